@@ -0,0 +1,104 @@
+//! The types shared by the runtime and the verifiers of its claims, which can't depend on the
+//! runtime without building it.
+//!
+//! Besides the basic types of the chain, this holds the claim records of the poe module and the
+//! derivation of their storage keys, which storage proofs are checked against.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use rstd::vec::Vec;
+use codec::{Encode, Decode};
+use sr_primitives::{generic, AnySignature, traits::{BlakeTwo256, Verify}};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+
+/// An index to a block.
+pub type BlockNumber = u32;
+
+/// Alias to 512-bit hash when used in the context of a transaction signature on the chain.
+pub type Signature = AnySignature;
+
+/// Some way of identifying an account on the chain. We intentionally make it equivalent
+/// to the public key of our transaction signing scheme.
+pub type AccountId = <Signature as Verify>::Signer;
+
+/// Balance of an account.
+pub type Balance = u128;
+
+/// A timestamp: milliseconds since the unix epoch.
+pub type Moment = u64;
+
+/// A hash of some data used by the chain.
+pub type Hash = primitives::H256;
+
+/// Block header type as expected by this runtime.
+pub type Header = generic::Header<BlockNumber, BlakeTwo256>;
+
+/// The record of a claim, as stored by the runtime.
+pub type Claim = ClaimInfo<AccountId, Moment, BlockNumber, Balance>;
+
+/// The hash algorithm a proof digest was computed with.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "lowercase"))]
+pub enum HashAlgorithm {
+	Sha256,
+	Blake2b256,
+	Keccak256,
+	Sha3_512,
+}
+
+impl HashAlgorithm {
+	/// The length, in bytes, of the digests produced by this algorithm.
+	pub fn digest_len(&self) -> usize {
+		match self {
+			HashAlgorithm::Sha256 | HashAlgorithm::Blake2b256 | HashAlgorithm::Keccak256 => 32,
+			HashAlgorithm::Sha3_512 => 64,
+		}
+	}
+
+	/// The algorithm assumed for an untagged digest of `len` bytes, if any: SHA-256 for 256-bit
+	/// digests, being the most common file hash, and SHA3-512 for 512-bit ones.
+	pub fn from_digest_len(len: usize) -> Option<Self> {
+		match len {
+			32 => Some(HashAlgorithm::Sha256),
+			64 => Some(HashAlgorithm::Sha3_512),
+			_ => None,
+		}
+	}
+}
+
+/// A proof digest, tagged with the hash algorithm that produced it.
+///
+/// `bytes` is bounded by `Trait::MaxDigestLength` and must be exactly as long as
+/// `algorithm` requires; both are checked by the module before a digest is used.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Digest {
+	pub algorithm: HashAlgorithm,
+	pub bytes: Vec<u8>,
+}
+
+/// Everything recorded on-chain about a claimed proof.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ClaimInfo<AccountId, Moment, BlockNumber, Balance> {
+	/// The account holding the claim.
+	pub owner: AccountId,
+	/// The time of the block the claim was created in, as set by its author.
+	pub moment: Moment,
+	/// The number of the block the claim was created in.
+	pub block_number: BlockNumber,
+	/// The index of the extrinsic that created the claim within its block.
+	pub extrinsic_index: u32,
+	/// The deposit reserved from the owner for holding the claim.
+	pub deposit: Balance,
+}
+
+/// The storage key of the `Proofs` entry for a digest, as laid out by the `PoeStorage` storage
+/// of the poe module.
+pub fn proof_key(digest: &Digest) -> [u8; 32] {
+	let mut key = b"PoeStorage Proofs".to_vec();
+	digest.encode_to(&mut key);
+	runtime_io::blake2_256(&key)
+}
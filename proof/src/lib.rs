@@ -0,0 +1,96 @@
+//! Verification of the storage proofs of proof-of-existence claims, for light clients and
+//! external verifiers.
+//!
+//! A claim proof is the list of trie nodes on the path to the `Proofs` entry of a digest, as
+//! returned by the `poe_getClaimProof` RPC. Checked against the state root of a trusted header, it
+//! shows whether the digest was claimed at that block, and by whom.
+//!
+//! This only depends on the primitives of the chain, so that verifiers don't need to build its
+//! runtime.
+
+use std::fmt;
+use codec::Decode;
+use primitives::Blake2Hasher;
+use sr_primitives::traits::Header as HeaderT;
+use poe_primitives::{Digest, Hash, Header, proof_key};
+
+pub use poe_primitives::Claim;
+
+/// Why a claim proof could not be checked.
+#[derive(Debug)]
+pub enum Error {
+	/// The proof is not a valid proof of the `Proofs` entry against the state root.
+	InvalidProof(String),
+	/// The proven entry is not a claim record.
+	InvalidClaim,
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::InvalidProof(err) => write!(f, "Invalid storage proof: {}", err),
+			Error::InvalidClaim => write!(f, "The storage proof holds an invalid claim record"),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// Check `proof` against the state root of `header`, returning the claim of `digest` at that
+/// block, or `None` if the proof shows that it was not claimed.
+pub fn verify_claim_proof(
+	header: &Header,
+	digest: &Digest,
+	proof: Vec<Vec<u8>>,
+) -> Result<Option<Claim>, Error> {
+	verify_claim_proof_at_root(*header.state_root(), digest, proof)
+}
+
+/// Check `proof` against a trusted state root, returning the claim of `digest` in that state, or
+/// `None` if the proof shows that it was not claimed.
+pub fn verify_claim_proof_at_root(
+	state_root: Hash,
+	digest: &Digest,
+	proof: Vec<Vec<u8>>,
+) -> Result<Option<Claim>, Error> {
+	let key = proof_key(digest);
+	let value = substrate_state_machine::read_proof_check::<Blake2Hasher>(state_root, proof, &key)
+		.map_err(|err| Error::InvalidProof(err.to_string()))?;
+
+	// Claims recorded before `ClaimInfo` are not readable until migrated
+	value.map(|value| Claim::decode(&mut &value[..]).map_err(|_| Error::InvalidClaim)).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use poe_primitives::HashAlgorithm;
+	use substrate_state_machine::{backend::{Backend, InMemory}, prove_read};
+
+	fn digest(byte: u8) -> Digest {
+		Digest { algorithm: HashAlgorithm::Sha256, bytes: vec![byte; 32] }
+	}
+
+	fn claim() -> Claim {
+		Claim { owner: Default::default(), moment: 42, block_number: 7, extrinsic_index: 1, deposit: 1000 }
+	}
+
+	#[test]
+	fn proofs_show_claims_and_their_absence() {
+		let backend = InMemory::<Blake2Hasher>::from(vec![
+			(None, proof_key(&digest(0)).to_vec(), Some(claim().encode())),
+			(None, b"unrelated".to_vec(), Some(vec![1])),
+		]);
+		let root = backend.storage_root(std::iter::empty()).0;
+
+		let (_, proof) = prove_read(backend.clone(), &proof_key(&digest(0))).unwrap();
+		assert_eq!(verify_claim_proof_at_root(root, &digest(0), proof.clone()).unwrap(), Some(claim()));
+
+		// The proof doesn't hold for another state
+		assert!(verify_claim_proof_at_root(Hash::repeat_byte(1), &digest(0), proof).is_err());
+
+		let (_, proof) = prove_read(backend, &proof_key(&digest(1))).unwrap();
+		assert_eq!(verify_claim_proof_at_root(root, &digest(1), proof).unwrap(), None);
+	}
+}
@@ -0,0 +1,556 @@
+//! Benchmarks of the proof-of-existence module, measuring how the cost of its dispatchables
+//! scales with the number of existing claims, the digest length and the batch size.
+//!
+//! Every dispatchable is benchmarked, in its most expensive case. Each benchmark populates fresh
+//! storage to the requested size, then dispatches the measured call natively and reports its
+//! execution time and the storage reads and writes it performed. The reads and writes can be
+//! turned into a weight with `poe::storage_weight`, to check or update the weights declared on the
+//! dispatchables. Storage is held in memory, so the time does not include database accesses.
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+use codec::{Encode, Decode};
+use primitives::{Blake2Hasher, H256, offchain, traits::BareCryptoStorePtr};
+use runtime_io::{with_externalities, ChildStorageKey, Externalities, TestExternalities};
+use sr_primitives::traits::Bounded;
+use sr_primitives::weights::{GetDispatchInfo, Weight};
+use support::dispatch::Dispatchable;
+use support::StorageValue;
+use support::traits::{Currency, Get};
+use crate::poe::{self, Call, ClaimMetadata, Digest, HashAlgorithm};
+
+/// The size of the storage and of the calls to benchmark.
+#[derive(Clone, Debug)]
+pub struct BenchmarkParams {
+	/// The number of claims populated in storage before measuring.
+	pub existing_claims: u32,
+	/// The algorithm of the claimed digests, which sets their length.
+	pub algorithm: HashAlgorithm,
+	/// The number of digests claimed at once by the `create_claims` benchmark.
+	pub batch_size: u32,
+	/// The number of measured calls, over which the results are averaged.
+	pub repeat: u32,
+}
+
+impl Default for BenchmarkParams {
+	fn default() -> Self {
+		BenchmarkParams { existing_claims: 1_000, algorithm: HashAlgorithm::Sha256, batch_size: 10, repeat: 20 }
+	}
+}
+
+/// The cost of a dispatchable, averaged over the measured calls.
+#[derive(Clone, Debug)]
+pub struct BenchmarkResult {
+	/// The name of the dispatchable.
+	pub call: &'static str,
+	/// The parameters it was measured with.
+	pub params: BenchmarkParams,
+	/// The average execution time of a call.
+	pub time: Duration,
+	/// The average number of storage reads of a call.
+	pub reads: u32,
+	/// The average number of storage writes of a call.
+	pub writes: u32,
+	/// The weight currently declared for the measured calls.
+	pub declared_weight: Weight,
+}
+
+impl BenchmarkResult {
+	/// The weight matching the measured storage accesses, to compare with `declared_weight`.
+	pub fn measured_weight(&self) -> Weight {
+		poe::storage_weight(self.reads as Weight, self.writes as Weight)
+	}
+}
+
+/// Run the benchmarks of every dispatchable of the module against runtime `T`, with the given
+/// parameters.
+///
+/// Fails if the batch size is not accepted by `create_claims`, or if a benchmarked call fails.
+pub fn run<T: poe::Trait>(params: &BenchmarkParams) -> Result<Vec<BenchmarkResult>, &'static str> {
+	if params.batch_size == 0 || params.batch_size > T::MaxBatchSize::get() {
+		return Err("The batch size must be between 1 and the maximum batch size of the runtime");
+	}
+
+	Ok(vec![
+		bench_create_claim::<T>(params)?,
+		bench_revoke_claim::<T>(params)?,
+		bench_set_claim_metadata::<T>(params)?,
+		bench_renew_claim::<T>(params)?,
+		bench_create_anchor::<T>(params)?,
+		bench_revoke_anchor::<T>(params)?,
+		bench_transfer_claim::<T>(params)?,
+		bench_offer_claim::<T>(params)?,
+		bench_accept_claim::<T>(params)?,
+		bench_cancel_claim_offer::<T>(params)?,
+		bench_force_remove_claim::<T>(params)?,
+		bench_blacklist_digest::<T>(params)?,
+		bench_unblacklist_digest::<T>(params)?,
+		bench_set_claim_deposit::<T>(params)?,
+		bench_set_claim_quota::<T>(params)?,
+		bench_migrate_claims::<T>(params)?,
+		bench_finish_migration::<T>(params)?,
+		bench_create_claims::<T>(params)?,
+	])
+}
+
+/// Format benchmark results as a table, one line per dispatchable.
+pub fn format_table(results: &[BenchmarkResult]) -> String {
+	let mut table = format!(
+		"{:<18} {:>8} {:>6} {:>6} {:>10} {:>6} {:>7} {:>9} {:>9}\n",
+		"call", "claims", "digest", "batch", "time (us)", "reads", "writes", "measured", "declared",
+	);
+	for result in results {
+		table.push_str(&format!(
+			"{:<18} {:>8} {:>6} {:>6} {:>10} {:>6} {:>7} {:>9} {:>9}\n",
+			result.call,
+			result.params.existing_claims,
+			result.params.algorithm.digest_len(),
+			result.params.batch_size,
+			result.time.as_micros(),
+			result.reads,
+			result.writes,
+			result.measured_weight(),
+			result.declared_weight,
+		));
+	}
+	table
+}
+
+// The most expensive claim to create: one expiring, with metadata.
+fn bench_create_claim<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let who = account::<T>(0);
+	measure::<T>("create_claim", params, |_| Ok(()), |i| {
+		let call = Call::<T>::create_claim(fresh(params, i), Some(expiry::<T>(1)), Some(metadata::<T>()));
+		(signed::<T>(&who), call)
+	})
+}
+
+fn bench_revoke_claim<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let who = account::<T>(0);
+	// Revoke existing claims if there are enough, or claim new digests to revoke first. The revoked
+	// claim is never the last one of its owner, so that the cost of filling its position is measured.
+	let claim_first = params.existing_claims < 2 * params.repeat.max(1);
+	let (base, step) = if claim_first { (params.existing_claims, 2) } else { (0, 1) };
+	measure::<T>("revoke_claim", params, |i| if claim_first {
+		dispatch::<T>(&who, Call::<T>::create_claims(vec![
+			digest(params.algorithm, base + 2 * i),
+			digest(params.algorithm, base + 2 * i + 1),
+		]))
+	} else {
+		Ok(())
+	}, |i| (signed::<T>(&who), Call::<T>::revoke_claim(digest(params.algorithm, base + step * i))))
+}
+
+// Adding metadata to a claim without any, which reserves a deposit for it.
+fn bench_set_claim_metadata<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let who = account::<T>(0);
+	measure::<T>("set_claim_metadata", params, |i| {
+		dispatch::<T>(&who, Call::<T>::create_claim(fresh(params, i), None, None))
+	}, |i| (signed::<T>(&who), Call::<T>::set_claim_metadata(fresh(params, i), Some(metadata::<T>()))))
+}
+
+// Extending the term of an expiring claim, which moves it to another block of `Expirations`.
+fn bench_renew_claim<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let who = account::<T>(0);
+	measure::<T>("renew_claim", params, |i| {
+		dispatch::<T>(&who, Call::<T>::create_claim(fresh(params, i), Some(expiry::<T>(1)), None))
+	}, |i| (signed::<T>(&who), Call::<T>::renew_claim(fresh(params, i), Some(expiry::<T>(2)))))
+}
+
+fn bench_create_anchor<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let who = account::<T>(0);
+	measure::<T>("create_anchor", params, |_| Ok(()), |i| {
+		(signed::<T>(&who), Call::<T>::create_anchor(merkle_root(i), u64::from(params.batch_size)))
+	})
+}
+
+// Anchors are created in pairs, so that revoking one never releases the last deposit of the owner.
+fn bench_revoke_anchor<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let who = account::<T>(0);
+	measure::<T>("revoke_anchor", params, |i| {
+		dispatch::<T>(&who, Call::<T>::create_anchor(merkle_root(2 * i), 1))?;
+		dispatch::<T>(&who, Call::<T>::create_anchor(merkle_root(2 * i + 1), 1))
+	}, |i| (signed::<T>(&who), Call::<T>::revoke_anchor(merkle_root(2 * i))))
+}
+
+fn bench_transfer_claim<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let (who, recipient) = (account::<T>(0), account::<T>(1));
+	measure::<T>("transfer_claim", params, |i| create_pair::<T>(params, i), |i| {
+		(signed::<T>(&who), Call::<T>::transfer_claim(fresh(params, 2 * i), recipient.clone()))
+	})
+}
+
+fn bench_offer_claim<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let who = account::<T>(0);
+	measure::<T>("offer_claim", params, |i| {
+		dispatch::<T>(&who, Call::<T>::create_claim(fresh(params, i), None, None))
+	}, |i| (signed::<T>(&who), offer::<T>(params, i)))
+}
+
+fn bench_accept_claim<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let (who, recipient) = (account::<T>(0), account::<T>(1));
+	measure::<T>("accept_claim", params, |i| {
+		create_pair::<T>(params, i)?;
+		dispatch::<T>(&who, offer::<T>(params, 2 * i))
+	}, |i| (signed::<T>(&recipient), Call::<T>::accept_claim(fresh(params, 2 * i))))
+}
+
+fn bench_cancel_claim_offer<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let who = account::<T>(0);
+	measure::<T>("cancel_claim_offer", params, |i| {
+		dispatch::<T>(&who, Call::<T>::create_claim(fresh(params, i), None, None))?;
+		dispatch::<T>(&who, offer::<T>(params, i))
+	}, |i| (signed::<T>(&who), Call::<T>::cancel_claim_offer(fresh(params, i))))
+}
+
+// Removing a claim without slashing its owner, which releases the deposit instead.
+fn bench_force_remove_claim<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	measure::<T>("force_remove_claim", params, |i| create_pair::<T>(params, i), |i| {
+		(root::<T>(), Call::<T>::force_remove_claim(fresh(params, 2 * i), false))
+	})
+}
+
+fn bench_blacklist_digest<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	measure::<T>("blacklist_digest", params, |_| Ok(()), |i| {
+		(root::<T>(), Call::<T>::blacklist_digest(fresh(params, i)))
+	})
+}
+
+fn bench_unblacklist_digest<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	measure::<T>("unblacklist_digest", params, |i| {
+		dispatch_root::<T>(Call::<T>::blacklist_digest(fresh(params, i)))
+	}, |i| (root::<T>(), Call::<T>::unblacklist_digest(fresh(params, i))))
+}
+
+fn bench_set_claim_deposit<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	measure::<T>("set_claim_deposit", params, |_| Ok(()), |_| {
+		(root::<T>(), Call::<T>::set_claim_deposit(T::ClaimDeposit::get()))
+	})
+}
+
+fn bench_set_claim_quota<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	measure::<T>("set_claim_quota", params, |_| Ok(()), |i| {
+		(root::<T>(), Call::<T>::set_claim_quota(account::<T>(2 + i), Some(params.batch_size)))
+	})
+}
+
+// Queueing a batch of legacy claims, on storage rolled back to the original layout.
+fn bench_migrate_claims<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	measure::<T>("migrate_claims", params, |_| rollback_storage_version::<T>(), |i| {
+		let first = params.existing_claims + i * params.batch_size;
+		let digests = (first..first + params.batch_size).map(|n| digest(params.algorithm, n).into()).collect();
+		(root::<T>(), Call::<T>::migrate_claims(digests))
+	})
+}
+
+fn bench_finish_migration<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	measure::<T>("finish_migration", params, |_| rollback_storage_version::<T>(), |_| {
+		(root::<T>(), Call::<T>::finish_migration())
+	})
+}
+
+fn bench_create_claims<T: poe::Trait>(params: &BenchmarkParams) -> Result<BenchmarkResult, &'static str> {
+	let who = account::<T>(0);
+	measure::<T>("create_claims", params, |_| Ok(()), |i| {
+		let first = params.existing_claims + i * params.batch_size;
+		let digests = (first..first + params.batch_size).map(|n| digest(params.algorithm, n)).collect();
+		(signed::<T>(&who), Call::<T>::create_claims(digests))
+	})
+}
+
+// On storage populated with `existing_claims` claims, dispatch `repeat` calls, built by `call` from
+// their index, and average their cost. `setup` is run before each call, without being measured.
+fn measure<T: poe::Trait>(
+	name: &'static str,
+	params: &BenchmarkParams,
+	setup: impl Fn(u32) -> Result<(), &'static str>,
+	call: impl Fn(u32) -> (T::Origin, Call<T>),
+) -> Result<BenchmarkResult, &'static str> {
+	let mut ext = populated_ext::<T>(params)?;
+	let repeat = params.repeat.max(1);
+	let (mut time, mut reads, mut writes, mut declared_weight) = (Duration::default(), 0, 0, 0);
+
+	for i in 0..repeat {
+		with_externalities(&mut ext, || setup(i))?;
+		let (origin, call) = call(i);
+		declared_weight = call.get_dispatch_info().weight;
+
+		let mut counting = CountingExternalities::new(&mut ext);
+		let start = Instant::now();
+		with_externalities(&mut counting, || call.dispatch(origin))?;
+		time += start.elapsed();
+		reads += counting.reads.get();
+		writes += counting.writes;
+	}
+
+	Ok(BenchmarkResult {
+		call: name,
+		params: params.clone(),
+		time: time / repeat,
+		reads: reads / repeat,
+		writes: writes / repeat,
+		declared_weight,
+	})
+}
+
+// Storage with `existing_claims` claims held by a funded account, which can hold any number of
+// claims, as can the funded account claims are transferred to.
+fn populated_ext<T: poe::Trait>(params: &BenchmarkParams) -> Result<TestExternalities<Blake2Hasher>, &'static str> {
+	let mut storage = system::GenesisConfig::default().build_storage::<T>()
+		.expect("the default system genesis config is valid; qed");
+	poe::GenesisConfig::default().assimilate_storage::<T>(&mut storage)
+		.expect("the default poe genesis config is valid; qed");
+	let mut ext: TestExternalities<Blake2Hasher> = storage.into();
+
+	with_externalities(&mut ext, || {
+		for n in 0..2 {
+			let who = account::<T>(n);
+			T::Currency::make_free_balance_be(&who, Bounded::max_value());
+			dispatch_root::<T>(Call::<T>::set_claim_quota(who, Some(u32::max_value())))?;
+		}
+
+		let who = account::<T>(0);
+		let batch = T::MaxBatchSize::get().max(1);
+		let mut next = 0;
+		while next < params.existing_claims {
+			let end = (next + batch).min(params.existing_claims);
+			let digests = (next..end).map(|n| digest(params.algorithm, n)).collect();
+			dispatch::<T>(&who, Call::<T>::create_claims(digests))?;
+			next = end;
+		}
+		Ok(())
+	})?;
+
+	Ok(ext)
+}
+
+// Claim the digests `fresh(params, 2 * i)` and the one after it, so that moving the first one
+// away never leaves its owner without claims, and the cost of filling its position is measured.
+fn create_pair<T: poe::Trait>(params: &BenchmarkParams, i: u32) -> Result<(), &'static str> {
+	dispatch::<T>(&account::<T>(0), Call::<T>::create_claims(vec![fresh(params, 2 * i), fresh(params, 2 * i + 1)]))
+}
+
+// An offer of the claim of `fresh(params, n)` to the second funded account, expiring after a term.
+fn offer<T: poe::Trait>(params: &BenchmarkParams, n: u32) -> Call<T> {
+	Call::<T>::offer_claim(fresh(params, n), account::<T>(1), Some(T::BlockNumber::from(TERM)))
+}
+
+// The `n`th digest not claimed by `populated_ext`.
+fn fresh(params: &BenchmarkParams, n: u32) -> Digest {
+	digest(params.algorithm, params.existing_claims + n)
+}
+
+// A distinct digest for each `n`, of the length of `algorithm`.
+fn digest(algorithm: HashAlgorithm, n: u32) -> Digest {
+	let mut bytes = runtime_io::blake2_256(&n.encode()).to_vec();
+	bytes.resize(algorithm.digest_len(), 0);
+	Digest { algorithm, bytes }
+}
+
+// A distinct Merkle root for each `n`.
+fn merkle_root(n: u32) -> H256 {
+	H256(runtime_io::blake2_256(&(b"poe-benchmark-anchor", n).encode()))
+}
+
+// The number of blocks benchmarked claims and offers last for.
+const TERM: u32 = 1_000;
+
+// The block a claim created now expires at, after `n` terms.
+fn expiry<T: poe::Trait>(n: u32) -> T::BlockNumber {
+	system::Module::<T>::block_number() + T::BlockNumber::from(TERM * n)
+}
+
+// Metadata filling half of `MaxMetadataLength`.
+fn metadata<T: poe::Trait>() -> ClaimMetadata {
+	let len = T::MaxMetadataLength::get() as usize / 2;
+	ClaimMetadata { name: None, content_type: None, size: Some(len as u64), uri: Some(vec![0; len]) }
+}
+
+// Roll the storage version back to the original layout, for the migration to be accepted.
+fn rollback_storage_version<T: poe::Trait>() -> Result<(), &'static str> {
+	poe::StorageVersion::put(0);
+	Ok(())
+}
+
+// A distinct account for each `n`.
+fn account<T: poe::Trait>(n: u32) -> T::AccountId {
+	let seed = H256(runtime_io::blake2_256(&(b"poe-benchmark", n).encode()));
+	T::AccountId::decode(&mut seed.as_bytes()).unwrap_or_default()
+}
+
+fn signed<T: poe::Trait>(who: &T::AccountId) -> T::Origin {
+	system::RawOrigin::Signed(who.clone()).into()
+}
+
+fn root<T: poe::Trait>() -> T::Origin {
+	system::RawOrigin::Root.into()
+}
+
+fn dispatch<T: poe::Trait>(who: &T::AccountId, call: Call<T>) -> Result<(), &'static str> {
+	call.dispatch(signed::<T>(who))
+}
+
+fn dispatch_root<T: poe::Trait>(call: Call<T>) -> Result<(), &'static str> {
+	call.dispatch(root::<T>())
+}
+
+// Externalities counting the storage reads and writes made through them.
+struct CountingExternalities<'a> {
+	inner: &'a mut TestExternalities<Blake2Hasher>,
+	reads: Cell<u32>,
+	writes: u32,
+}
+
+impl<'a> CountingExternalities<'a> {
+	fn new(inner: &'a mut TestExternalities<Blake2Hasher>) -> Self {
+		CountingExternalities { inner, reads: Cell::new(0), writes: 0 }
+	}
+
+	fn read(&self) {
+		self.reads.set(self.reads.get() + 1);
+	}
+}
+
+impl<'a> Externalities<Blake2Hasher> for CountingExternalities<'a> {
+	fn storage(&self, key: &[u8]) -> Option<Vec<u8>> {
+		self.read();
+		self.inner.storage(key)
+	}
+
+	fn original_storage(&self, key: &[u8]) -> Option<Vec<u8>> {
+		self.read();
+		self.inner.original_storage(key)
+	}
+
+	fn child_storage(&self, storage_key: ChildStorageKey<Blake2Hasher>, key: &[u8]) -> Option<Vec<u8>> {
+		self.read();
+		self.inner.child_storage(storage_key, key)
+	}
+
+	fn place_storage(&mut self, key: Vec<u8>, value: Option<Vec<u8>>) {
+		self.writes += 1;
+		self.inner.place_storage(key, value)
+	}
+
+	fn place_child_storage(
+		&mut self,
+		storage_key: ChildStorageKey<Blake2Hasher>,
+		key: Vec<u8>,
+		value: Option<Vec<u8>>,
+	) {
+		self.writes += 1;
+		self.inner.place_child_storage(storage_key, key, value)
+	}
+
+	fn kill_child_storage(&mut self, storage_key: ChildStorageKey<Blake2Hasher>) {
+		self.writes += 1;
+		self.inner.kill_child_storage(storage_key)
+	}
+
+	fn clear_prefix(&mut self, prefix: &[u8]) {
+		self.writes += 1;
+		self.inner.clear_prefix(prefix)
+	}
+
+	fn chain_id(&self) -> u64 {
+		self.inner.chain_id()
+	}
+
+	fn storage_root(&mut self) -> H256 {
+		self.inner.storage_root()
+	}
+
+	fn child_storage_root(&mut self, storage_key: ChildStorageKey<Blake2Hasher>) -> Vec<u8> {
+		self.inner.child_storage_root(storage_key)
+	}
+
+	fn storage_changes_root(&mut self, parent: H256) -> Result<Option<H256>, ()> {
+		self.inner.storage_changes_root(parent)
+	}
+
+	fn offchain(&mut self) -> Option<&mut dyn offchain::Externalities> {
+		self.inner.offchain()
+	}
+
+	fn keystore(&self) -> Option<BareCryptoStorePtr> {
+		self.inner.keystore()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use support::metadata::DecodeDifferent;
+	use crate::Runtime;
+
+	fn params(existing_claims: u32, algorithm: HashAlgorithm, batch_size: u32) -> BenchmarkParams {
+		BenchmarkParams { existing_claims, algorithm, batch_size, repeat: 3 }
+	}
+
+	#[test]
+	fn storage_accesses_do_not_depend_on_existing_claims() {
+		let empty = run::<Runtime>(&params(0, HashAlgorithm::Sha256, 2)).unwrap();
+		let populated = run::<Runtime>(&params(50, HashAlgorithm::Sha256, 2)).unwrap();
+
+		for (empty, populated) in empty.iter().zip(&populated) {
+			assert!(empty.reads > 0 && empty.writes > 0, "{} accesses storage", empty.call);
+			assert_eq!((empty.reads, empty.writes), (populated.reads, populated.writes), "{}", empty.call);
+		}
+	}
+
+	#[test]
+	fn every_dispatchable_is_benchmarked() {
+		let results = run::<Runtime>(&params(0, HashAlgorithm::Sha256, 2)).unwrap();
+
+		for function in poe::Module::<Runtime>::call_functions() {
+			let name = match &function.name {
+				DecodeDifferent::Encode(name) => *name,
+				DecodeDifferent::Decoded(name) => name.as_str(),
+			};
+			assert!(results.iter().any(|result| result.call == name), "{} is benchmarked", name);
+		}
+	}
+
+	#[test]
+	fn table_has_a_line_per_call() {
+		let results = run::<Runtime>(&params(10, HashAlgorithm::Sha3_512, 3)).unwrap();
+		let table = format_table(&results);
+
+		assert_eq!(table.lines().count(), 1 + results.len());
+		for result in &results {
+			assert_eq!(result.params.algorithm.digest_len(), 64);
+			assert!(table.contains(result.call));
+		}
+	}
+
+	#[test]
+	fn declared_weights_cover_measured_accesses() {
+		for existing_claims in &[0, 50] {
+			for algorithm in &[HashAlgorithm::Sha256, HashAlgorithm::Sha3_512] {
+				for result in run::<Runtime>(&params(*existing_claims, *algorithm, 4)).unwrap() {
+					assert!(
+						result.declared_weight >= result.measured_weight(),
+						"{} declares {} but measured {}", result.call, result.declared_weight, result.measured_weight(),
+					);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn batch_accesses_scale_with_batch_size() {
+		let single = run::<Runtime>(&params(0, HashAlgorithm::Sha256, 1)).unwrap().pop().unwrap();
+		let batch = run::<Runtime>(&params(0, HashAlgorithm::Sha256, 4)).unwrap().pop().unwrap();
+		assert_eq!(batch.call, "create_claims");
+		assert!(batch.writes > single.writes);
+		assert!(batch.declared_weight > single.declared_weight);
+	}
+
+	#[test]
+	fn batch_size_must_be_accepted_by_the_runtime() {
+		let max = <Runtime as poe::Trait>::MaxBatchSize::get();
+		assert!(run::<Runtime>(&params(0, HashAlgorithm::Sha256, 0)).is_err());
+		assert!(run::<Runtime>(&params(0, HashAlgorithm::Sha256, max + 1)).is_err());
+		assert!(run::<Runtime>(&params(0, HashAlgorithm::Sha256, max)).is_ok());
+	}
+}
@@ -12,9 +12,9 @@ use rstd::prelude::*;
 use primitives::{OpaqueMetadata, crypto::key_types};
 use sr_primitives::{
 	ApplyResult, transaction_validity::TransactionValidity, generic, create_runtime_str,
-	impl_opaque_keys, AnySignature
+	impl_opaque_keys,
 };
-use sr_primitives::traits::{NumberFor, BlakeTwo256, Block as BlockT, DigestFor, StaticLookup, Verify, ConvertInto};
+use sr_primitives::traits::{NumberFor, BlakeTwo256, Block as BlockT, DigestFor, StaticLookup, ConvertInto};
 use sr_primitives::weights::Weight;
 use babe::{AuthorityId as BabeId};
 use grandpa::{AuthorityId as GrandpaId, AuthorityWeight as GrandpaWeight};
@@ -34,35 +34,27 @@ pub use timestamp::Call as TimestampCall;
 pub use balances::Call as BalancesCall;
 pub use sr_primitives::{Permill, Perbill};
 pub use support::{StorageValue, construct_runtime, parameter_types};
-
-/// An index to a block.
-pub type BlockNumber = u32;
-
-/// Alias to 512-bit hash when used in the context of a transaction signature on the chain.
-pub type Signature = AnySignature;
-
-/// Some way of identifying an account on the chain. We intentionally make it equivalent
-/// to the public key of our transaction signing scheme.
-pub type AccountId = <Signature as Verify>::Signer;
+pub use poe_primitives::{AccountId, Balance, BlockNumber, Hash, Header, Moment, Signature};
 
 /// The type for looking up accounts. We don't expect more than 4 billion of them, but you
 /// never know...
 pub type AccountIndex = u32;
 
-/// Balance of an account.
-pub type Balance = u128;
-
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
-/// A hash of some data used by the chain.
-pub type Hash = primitives::H256;
-
 /// Digest item type.
 pub type DigestItem = generic::DigestItem<Hash>;
 
 /// Used for the module template in `./template.rs`
-mod poe;
+pub mod poe;
+/// Merkle trees used to anchor batches of documents with the PoE module.
+pub mod merkle;
+/// Runtime APIs of the PoE module.
+pub mod poe_api;
+/// Benchmarks of the PoE module, used to derive the weights of its dispatchables.
+#[cfg(feature = "std")]
+pub mod benchmark;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
@@ -97,8 +89,8 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("substrate-poe"),
 	impl_name: create_runtime_str!("substrate-poe"),
 	authoring_version: 3,
-	spec_version: 4,
-	impl_version: 4,
+	spec_version: 36,
+	impl_version: 36,
 	apis: RUNTIME_API_VERSIONS,
 };
 
@@ -213,7 +205,7 @@ parameter_types! {
 
 impl timestamp::Trait for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
-	type Moment = u64;
+	type Moment = Moment;
 	type OnTimestampSet = Babe;
 	type MinimumPeriod = MinimumPeriod;
 }
@@ -252,10 +244,33 @@ impl sudo::Trait for Runtime {
 	type Proposal = Call;
 }
 
+parameter_types! {
+	pub const ClaimDeposit: Balance = 1000;
+	pub const MaxDigestLength: u32 = 100;
+	pub const MigrationBatchSize: u32 = 100;
+	// A full batch of digests of `MaxDigestLength`, weighing 1,100 per digest, must fit in half of
+	// the normal share of a block, leaving room for other transactions
+	pub const MaxBatchSize: u32 = 250;
+	pub const MaxClaimsPerAccount: u32 = 10_000;
+	pub const MaxExpirationsPerBlock: u32 = 100;
+	pub const MaxMetadataLength: u32 = 512;
+	pub const DepositPerByte: Balance = 1;
+}
+
 /// Used for the module template in `./template.rs`
 impl poe::Trait for Runtime {
 	type Currency = Balances;
 	type Event = Event;
+	type ClaimDeposit = ClaimDeposit;
+	type MaxDigestLength = MaxDigestLength;
+	type MigrationBatchSize = MigrationBatchSize;
+	type MaxBatchSize = MaxBatchSize;
+	type MaxClaimsPerAccount = MaxClaimsPerAccount;
+	type MaxExpirationsPerBlock = MaxExpirationsPerBlock;
+	type MaxMetadataLength = MaxMetadataLength;
+	type DepositPerByte = DepositPerByte;
+	// There is no treasury on this chain, slashed deposits are burnt
+	type OnSlash = ();
 }
 
 construct_runtime!(
@@ -272,14 +287,12 @@ construct_runtime!(
 		Balances: balances,
 		Sudo: sudo,
 		// Used for the module PoE in `./poe.rs`
-		Poe: poe::{Module, Call, Storage, Event<T>},
+		Poe: poe::{Module, Call, Storage, Event<T>, Config},
 	}
 );
 
 /// The address format for describing accounts.
 pub type Address = <Indices as StaticLookup>::Source;
-/// Block header type as expected by this runtime.
-pub type Header = generic::Header<BlockNumber, BlakeTwo256>;
 /// Block type as expected by this runtime.
 pub type Block = generic::Block<Header, UncheckedExtrinsic>;
 /// A Block signed with a Justification
@@ -293,7 +306,8 @@ pub type SignedExtra = (
 	system::CheckEra<Runtime>,
 	system::CheckNonce<Runtime>,
 	system::CheckWeight<Runtime>,
-	balances::TakeFees<Runtime>
+	balances::TakeFees<Runtime>,
+	poe::CheckPoeClaim<Runtime>
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -401,6 +415,26 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl poe_api::AnchorApi<Block> for Runtime {
+		fn verify_inclusion(root: Hash, leaf: poe::Digest, proof: Vec<Hash>) -> bool {
+			Poe::verify_inclusion(&root, &leaf, &proof)
+		}
+	}
+
+	impl poe_api::PoeApi<Block, AccountId, Moment, BlockNumber, Balance> for Runtime {
+		fn claim(digest: poe::Digest) -> Option<poe::ClaimInfo<AccountId, Moment, BlockNumber, Balance>> {
+			Poe::claim(&digest)
+		}
+
+		fn claims_of(account: AccountId, start: u32, count: u32) -> Vec<poe::Digest> {
+			Poe::claims_of(&account, start, count)
+		}
+
+		fn stats() -> poe::PoeStats {
+			Poe::stats()
+		}
+	}
+
 	impl substrate_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			let seed = seed.as_ref().map(|s| rstd::str::from_utf8(&s).expect("Seed is an utf8 string"));
@@ -408,3 +442,24 @@ impl_runtime_apis! {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sr_primitives::weights::{DispatchClass, GetDispatchInfo};
+	use support::traits::Get;
+
+	#[test]
+	fn full_claim_batch_fits_in_half_a_block() {
+		let digest = poe::Digest {
+			algorithm: poe::HashAlgorithm::Sha3_512,
+			bytes: vec![0; MaxDigestLength::get() as usize],
+		};
+		let batch = Call::Poe(poe::Call::create_claims(vec![digest; MaxBatchSize::get() as usize]));
+		let info = batch.get_dispatch_info();
+
+		let limit = AvailableBlockRatio::get() * MaximumBlockWeight::get() / 2;
+		assert_eq!(info.class, DispatchClass::Normal);
+		assert!(info.weight <= limit, "a full batch weighs {}, over the limit of {}", info.weight, limit);
+	}
+}
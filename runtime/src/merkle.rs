@@ -0,0 +1,158 @@
+//! Binary Merkle trees over proof digests, so that many documents can be anchored on-chain
+//! with a single root.
+//!
+//! Leaves are the Blake2-256 hashes of the encoded digests. Inner nodes hash their two children
+//! sorted, so a proof is just the list of sibling hashes from the leaf up to the root. A node
+//! without sibling is promoted as is to the next level.
+
+use rstd::vec::Vec;
+use primitives::H256;
+use codec::Encode;
+use crate::poe::Digest;
+
+// Prefixes telling leaves and inner nodes apart, so that a node can't be passed off as a leaf.
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// The hash of the leaf of a digest.
+pub fn leaf_hash(digest: &Digest) -> H256 {
+	let mut data = Vec::with_capacity(digest.bytes.len() + 3);
+	data.push(LEAF_PREFIX);
+	digest.encode_to(&mut data);
+	H256(runtime_io::blake2_256(&data))
+}
+
+/// The hash of the parent of two nodes.
+pub fn node_hash(a: &H256, b: &H256) -> H256 {
+	let (left, right) = if a <= b { (a, b) } else { (b, a) };
+	let mut data = Vec::with_capacity(65);
+	data.push(NODE_PREFIX);
+	data.extend_from_slice(left.as_bytes());
+	data.extend_from_slice(right.as_bytes());
+	H256(runtime_io::blake2_256(&data))
+}
+
+/// The maximum length of an inclusion proof in a tree of `leaf_count` leaves.
+pub fn max_proof_len(leaf_count: u64) -> usize {
+	match leaf_count {
+		0 => 0,
+		n => (64 - (n - 1).leading_zeros()) as usize,
+	}
+}
+
+/// Whether `leaf` belongs to the tree of `root`, given the sibling hashes in `proof`.
+pub fn verify_inclusion(root: &H256, leaf: &Digest, proof: &[H256]) -> bool {
+	proof.iter().fold(leaf_hash(leaf), |node, sibling| node_hash(&node, sibling)) == *root
+}
+
+/// A Merkle tree built from a list of digests, giving the root to anchor and the inclusion
+/// proof of each digest.
+#[cfg(feature = "std")]
+pub struct MerkleTree {
+	// All the levels of the tree, from the leaves up to the root.
+	levels: Vec<Vec<H256>>,
+}
+
+#[cfg(feature = "std")]
+impl MerkleTree {
+	/// Build the tree of the given digests, in order. Returns `None` if there are none.
+	pub fn new(digests: &[Digest]) -> Option<Self> {
+		if digests.is_empty() {
+			return None;
+		}
+
+		let mut levels = vec![digests.iter().map(leaf_hash).collect::<Vec<_>>()];
+		while levels[levels.len() - 1].len() > 1 {
+			let next = levels[levels.len() - 1]
+				.chunks(2)
+				.map(|pair| match pair {
+					[left, right] => node_hash(left, right),
+					[single] => *single,
+					_ => unreachable!("chunks are of one or two nodes; qed"),
+				})
+				.collect();
+			levels.push(next);
+		}
+
+		Some(MerkleTree { levels })
+	}
+
+	/// The root of the tree.
+	pub fn root(&self) -> H256 {
+		self.levels[self.levels.len() - 1][0]
+	}
+
+	/// The number of leaves of the tree.
+	pub fn leaf_count(&self) -> u64 {
+		self.levels[0].len() as u64
+	}
+
+	/// The inclusion proof of the digest at `index`, if there is one.
+	pub fn proof(&self, mut index: usize) -> Option<Vec<H256>> {
+		if index >= self.levels[0].len() {
+			return None;
+		}
+
+		let mut proof = Vec::new();
+		for level in &self.levels[..self.levels.len() - 1] {
+			if let Some(sibling) = level.get(index ^ 1) {
+				proof.push(*sibling);
+			}
+			index /= 2;
+		}
+
+		Some(proof)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::poe::HashAlgorithm;
+
+	fn digest(byte: u8) -> Digest {
+		Digest { algorithm: HashAlgorithm::Sha256, bytes: vec![byte; 32] }
+	}
+
+	#[test]
+	fn every_leaf_has_a_valid_proof() {
+		for count in 1..=9 {
+			let digests: Vec<_> = (0..count).map(digest).collect();
+			let tree = MerkleTree::new(&digests).unwrap();
+			assert_eq!(tree.leaf_count(), count as u64);
+
+			for (index, leaf) in digests.iter().enumerate() {
+				let proof = tree.proof(index).unwrap();
+				assert!(proof.len() <= max_proof_len(tree.leaf_count()));
+				assert!(verify_inclusion(&tree.root(), leaf, &proof));
+			}
+			assert_eq!(tree.proof(count as usize), None);
+		}
+	}
+
+	#[test]
+	fn proofs_do_not_verify_other_leaves() {
+		let digests: Vec<_> = (0..5).map(digest).collect();
+		let tree = MerkleTree::new(&digests).unwrap();
+
+		let proof = tree.proof(1).unwrap();
+		assert!(!verify_inclusion(&tree.root(), &digest(0), &proof));
+		assert!(!verify_inclusion(&tree.root(), &digest(5), &proof));
+
+		// The same bytes hashed with another algorithm are another leaf
+		let other = Digest { algorithm: HashAlgorithm::Blake2b256, bytes: vec![1; 32] };
+		assert!(!verify_inclusion(&tree.root(), &other, &proof));
+	}
+
+	#[test]
+	fn single_leaf_tree_and_empty_list() {
+		assert!(MerkleTree::new(&[]).is_none());
+
+		let tree = MerkleTree::new(&[digest(0)]).unwrap();
+		assert_eq!(tree.root(), leaf_hash(&digest(0)));
+		assert_eq!(tree.proof(0), Some(vec![]));
+		assert_eq!(max_proof_len(1), 0);
+		assert_eq!(max_proof_len(2), 1);
+		assert_eq!(max_proof_len(5), 3);
+	}
+}
@@ -1,34 +1,322 @@
 /// A runtime module for a simple Proof-of-existence mechanism.
 
-use support::{decl_module, decl_storage, decl_event, ensure, StorageMap, dispatch::Result};
-use support::traits::{Currency, ReservableCurrency};
-use rstd::vec::Vec;
-use system::ensure_signed;
+use support::{
+	decl_module, decl_storage, decl_event, ensure, StorageMap, StorageDoubleMap, StorageValue, dispatch::Result,
+};
+use support::traits::{Currency, ReservableCurrency, Get, Imbalance, OnUnbalanced};
+use support::dispatch::IsSubType;
+use rstd::{marker::PhantomData, vec::Vec};
+use sr_primitives::traits::{One, Saturating, SignedExtension, Zero};
+use sr_primitives::transaction_validity::{
+	InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+};
+use sr_primitives::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, DispatchInfo, SimpleDispatchInfo};
+use system::{ensure_signed, ensure_root};
+use codec::{Encode, Decode};
+use primitives::H256;
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+use crate::merkle;
 
-pub const ERR_DIGEST_TOO_LONG: &str = "Digest too long (max 100 bytes)";
-pub const DIGEST_MAXSIZE: usize = 100;
+pub use poe_primitives::{ClaimInfo, Digest, HashAlgorithm};
 
-// Fee that users are supposed to deposit to
-// hold a claim on a specific proof digest
-const POE_FEE: u32 = 1000;
+// The actual bound is the `MaxDigestLength` constant exposed in the module metadata.
+pub const ERR_DIGEST_TOO_LONG: &str = "Digest too long (exceeds MaxDigestLength)";
+pub const ERR_DIGEST_BAD_LENGTH: &str = "Digest length does not match its hash algorithm";
+pub const ERR_QUOTA_EXCEEDED: &str = "Too many claims held by this account (exceeds its claim quota)";
+pub const ERR_EXPIRY_IN_PAST: &str = "Expiry must be a future block";
+pub const ERR_METADATA_TOO_LONG: &str = "Claim metadata too long (exceeds MaxMetadataLength)";
+pub const ERR_BLACKLISTED: &str = "This digest has been blacklisted";
+
+/// Custom error code of transactions rejected by `CheckPoeClaim` because they claim a
+/// blacklisted digest.
+pub const INVALID_BLACKLISTED: u8 = 1;
+/// Custom error code of transactions rejected by `CheckPoeClaim` because they claim a digest
+/// that has already been claimed.
+pub const INVALID_ALREADY_CLAIMED: u8 = 2;
+/// Custom error code of transactions rejected by `CheckPoeClaim` because they revoke a claim
+/// not held by their sender.
+pub const INVALID_NOT_OWNER: u8 = 3;
+
+// Prefix of the `provides` tag of transactions claiming a digest.
+const CLAIM_TAG_PREFIX: &[u8] = b"poe:claim";
+
+/// The version of the storage layout used by this module:
+/// - 0: claims stored as `(AccountId, Moment)`, deposits recorded in `ClaimDeposits`. Chains started
+///   before digests were tagged with their algorithm key both by the digest bytes alone.
+/// - 1: claims stored as `ClaimInfo`. Legacy claims that were not queued for migration are still
+///   read and migrated when touched.
+pub const STORAGE_VERSION: u32 = 1;
+
+// Deposit that was reserved for claims created before the deposit became configurable.
+// Claims without a recorded deposit are released with this amount.
+const LEGACY_CLAIM_DEPOSIT: u32 = 1000;
+
+// Prefixes of the storage keys of `Proofs` and `ClaimDeposits` entries, which chains started before
+// `Digest` was introduced hash with the untagged digest bytes instead of a `Digest`.
+const UNTAGGED_PROOFS_PREFIX: &[u8] = b"PoeStorage Proofs";
+const UNTAGGED_DEPOSITS_PREFIX: &[u8] = b"PoeStorage ClaimDeposits";
 
 // Shorthand type for Balance type from Currency trait
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+// Shorthand type for the claim record stored by this module
+pub type ClaimInfoOf<T> = ClaimInfo<
+	<T as system::Trait>::AccountId,
+	<T as timestamp::Trait>::Moment,
+	<T as system::Trait>::BlockNumber,
+	BalanceOf<T>,
+>;
+
+// Layout of a claim record before `ClaimInfo` was introduced: the owner and the block time.
+type LegacyClaimOf<T> = (<T as system::Trait>::AccountId, <T as timestamp::Trait>::Moment);
+
+/// A digest claimed before the current storage layout, to be migrated with `migrate_claims`.
+///
+/// Claims of the original layout were keyed by their digest bytes alone. Without an `algorithm`,
+/// it is inferred from the length of `bytes`, see `HashAlgorithm::from_digest_len`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct LegacyDigest {
+	pub algorithm: Option<HashAlgorithm>,
+	pub bytes: Vec<u8>,
+}
+
+impl LegacyDigest {
+	/// The digest the claim is stored under once migrated. Digests whose length matches no
+	/// algorithm are tagged as SHA-256: they fail `ensure_valid_digest`, so that such claims can
+	/// only be removed with `force_remove_claim`.
+	pub fn into_digest(self) -> Digest {
+		let algorithm = self.algorithm
+			.or_else(|| HashAlgorithm::from_digest_len(self.bytes.len()))
+			.unwrap_or(HashAlgorithm::Sha256);
+		Digest { algorithm, bytes: self.bytes }
+	}
+}
+
+impl From<Digest> for LegacyDigest {
+	fn from(digest: Digest) -> Self {
+		LegacyDigest { algorithm: Some(digest.algorithm), bytes: digest.bytes }
+	}
+}
+
+/// Optional context about the document behind a claim, for auditors. Its encoded length is
+/// bounded by `Trait::MaxMetadataLength`, and each byte is covered by the claim's deposit.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ClaimMetadata {
+	/// The name of the file.
+	pub name: Option<Vec<u8>>,
+	/// The MIME type of the file.
+	pub content_type: Option<Vec<u8>>,
+	/// The size of the file, in bytes.
+	pub size: Option<u64>,
+	/// Where the file can be found, e.g. an IPFS CID.
+	pub uri: Option<Vec<u8>>,
+}
+
+/// Figures about the proofs recorded by the module.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct PoeStats {
+	/// The number of claimed digests, not counting legacy claims that are yet to be migrated.
+	pub claims: u64,
+	/// The number of anchored Merkle roots.
+	pub anchors: u64,
+}
+
+/// A pending offer to transfer a claim, waiting to be accepted by its recipient.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ClaimOffer<AccountId, BlockNumber> {
+	/// The account the claim is offered to.
+	pub recipient: AccountId,
+	/// The block after which the offer can no longer be accepted, if any.
+	pub expires_at: Option<BlockNumber>,
+}
+
+// Weights are in microseconds of execution, a block allowing `MaximumBlockWeight` of them. The
+// dispatchables of this module are dominated by their storage accesses, including those of the
+// balances and system modules: their counts are those reported by the `benchmark` module for
+// every dispatchable, whose tests check that the declared weights cover them.
+//
+// The benchmark runs on storage held in memory, so it can't measure the cost of an access. The
+// costs below are not measured either: they are estimates for a RocksDB database on an SSD, a
+// read usually hitting the cache and a write being committed to disk with the block. The time
+// reported by the benchmark is a lower bound to check them against on the hardware of validators.
+
+/// Weight of reading an item from the database, estimated at 25µs.
+pub const READ_WEIGHT: Weight = 25;
+/// Weight of writing an item to the database, estimated at 100µs.
+pub const WRITE_WEIGHT: Weight = 100;
+/// Weight of each byte of call argument hashed into a storage key or stored.
+pub const BYTE_WEIGHT: Weight = 1;
+
+/// The weight of a dispatchable doing the given number of storage reads and writes.
+pub const fn storage_weight(reads: Weight, writes: Weight) -> Weight {
+	reads * READ_WEIGHT + writes * WRITE_WEIGHT
+}
+
+/// Weight of a dispatchable creating a batch of claims: a base weight, plus a fixed weight per
+/// claim and the length of each digest.
+pub struct WeightPerClaim(pub Weight, pub Weight);
+
+impl<'a> WeighData<(&'a Vec<Digest>,)> for WeightPerClaim {
+	fn weigh_data(&self, (digests,): (&'a Vec<Digest>,)) -> Weight {
+		digests.iter().fold(self.0, |weight: Weight, digest| {
+			let bytes = BYTE_WEIGHT.saturating_mul(digest.bytes.len() as Weight);
+			weight.saturating_add(self.1.saturating_add(bytes))
+		})
+	}
+}
+
+impl<T> ClassifyDispatch<T> for WeightPerClaim {
+	fn classify_dispatch(&self, _: T) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+/// Weight of a dispatchable working on a single claim: the weight of its storage accesses,
+/// plus the encoded length of its arguments, starting with the digest.
+pub struct ClaimWeight(pub Weight);
+
+impl ClaimWeight {
+	fn with_bytes(&self, len: usize) -> Weight {
+		self.0.saturating_add(BYTE_WEIGHT.saturating_mul(len as Weight))
+	}
+}
+
+impl<'a> WeighData<(&'a Digest,)> for ClaimWeight {
+	fn weigh_data(&self, (digest,): (&'a Digest,)) -> Weight {
+		self.with_bytes(digest.bytes.len())
+	}
+}
+
+impl<'a, 'b, A: Encode> WeighData<(&'a Digest, &'b A)> for ClaimWeight {
+	fn weigh_data(&self, (digest, a): (&'a Digest, &'b A)) -> Weight {
+		self.with_bytes(digest.bytes.len() + a.encode().len())
+	}
+}
+
+impl<'a, 'b, 'c, A: Encode, B: Encode> WeighData<(&'a Digest, &'b A, &'c B)> for ClaimWeight {
+	fn weigh_data(&self, (digest, a, b): (&'a Digest, &'b A, &'c B)) -> Weight {
+		self.with_bytes(digest.bytes.len() + a.encode().len() + b.encode().len())
+	}
+}
+
+impl<T> ClassifyDispatch<T> for ClaimWeight {
+	fn classify_dispatch(&self, _: T) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+/// Weight of queueing legacy claims for migration: a fixed weight, plus the write of each digest
+/// and its length. Operational, as only root can queue claims.
+pub struct MigrationWeight(pub Weight);
+
+impl<'a> WeighData<(&'a Vec<LegacyDigest>,)> for MigrationWeight {
+	fn weigh_data(&self, (digests,): (&'a Vec<LegacyDigest>,)) -> Weight {
+		digests.iter().fold(self.0, |weight: Weight, digest| {
+			let bytes = BYTE_WEIGHT.saturating_mul(digest.bytes.len() as Weight);
+			weight.saturating_add(WRITE_WEIGHT.saturating_add(bytes))
+		})
+	}
+}
+
+impl<T> ClassifyDispatch<T> for MigrationWeight {
+	fn classify_dispatch(&self, _: T) -> DispatchClass {
+		DispatchClass::Operational
+	}
+}
 
 /// The module's configuration trait.
 pub trait Trait: timestamp::Trait {
 	type Currency: ReservableCurrency<Self::AccountId>;
 	/// The overarching event type.
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// The base deposit users have to reserve to hold a claim on a proof digest,
+	/// unless it has been overridden on-chain with `set_claim_deposit`.
+	type ClaimDeposit: Get<BalanceOf<Self>>;
+	/// The maximum length, in bytes, of a proof digest.
+	type MaxDigestLength: Get<u32>;
+	/// The maximum number of claims migrated to the current storage layout per block.
+	type MigrationBatchSize: Get<u32>;
+	/// The maximum number of digests that can be claimed at once with `create_claims`, or queued
+	/// at once for migration with `migrate_claims`.
+	type MaxBatchSize: Get<u32>;
+	/// The maximum number of claims an account can hold, unless granted a higher quota
+	/// with `set_claim_quota`.
+	type MaxClaimsPerAccount: Get<u32>;
+	/// The maximum number of expiration entries handled per block, each block without entries left
+	/// counting as one. Leftovers are handled in the following blocks.
+	type MaxExpirationsPerBlock: Get<u32>;
+	/// The maximum encoded length, in bytes, of the metadata of a claim.
+	type MaxMetadataLength: Get<u32>;
+	/// The deposit reserved for each byte of a stored claim (digest, record and metadata),
+	/// on top of the base claim deposit.
+	type DepositPerByte: Get<BalanceOf<Self>>;
+	/// Handler for the deposits slashed by `force_remove_claim`, e.g. a treasury.
+	/// With `()`, they are burnt.
+	type OnSlash: OnUnbalanced<NegativeImbalanceOf<Self>>;
 }
 
 // This module's storage items.
 decl_storage! {
 	trait Store for Module<T: Trait> as PoeStorage {
 		// Define a 'Proofs' storage space for a map with
-		// the proof digest as the key, and associated AccountId as value.
-		// The 'get(proofs)' is the default getter.
-		Proofs get(proofs): map Vec<u8> => (T::AccountId, T::Moment);
+		// the proof digest as the key, and the claim record as value.
+		// There is no getter: entries may still use the legacy `(AccountId, Moment)` layout,
+		// or be keyed by the untagged digest bytes, so they must be read through `Module::claim`.
+		Proofs: map Digest => Option<ClaimInfoOf<T>>;
+		// The deposit reserved for claims stored with the legacy layout. It is folded into
+		// `ClaimInfo` when such a claim is migrated, and no longer written otherwise.
+		ClaimDeposits get(claim_deposit_of): map Digest => Option<BalanceOf<T>>;
+		// The digests claimed by each account, at positions 0 to `OwnedClaimCount` excluded. Removing
+		// a claim moves the last one of its owner to its position. Legacy claims are indexed once
+		// migrated to the current storage layout.
+		ClaimsByOwner get(owned_claim): double_map T::AccountId, blake2_256(u32) => Option<Digest>;
+		// The position of each indexed digest in the claims of its owner.
+		OwnedClaimPosition get(owned_claim_position): map Digest => Option<u32>;
+		// The number of claims held by each account, checked against its quota.
+		OwnedClaimCount get(owned_claim_count): map T::AccountId => u32;
+		// Accounts granted a quota other than `MaxClaimsPerAccount`, set by `set_claim_quota`.
+		ClaimQuotaOverride get(claim_quota_override): map T::AccountId => Option<u32>;
+		// The number of claimed digests, legacy claims being counted once migrated.
+		ClaimCount get(claim_count): u64;
+		// The number of anchored Merkle roots.
+		AnchorCount get(anchor_count): u64;
+		// Anchored Merkle roots of batches of documents, with the claim record of the anchor
+		// and the number of leaves of its tree. See the `merkle` module for how trees are built.
+		Anchors get(anchor): map H256 => Option<(ClaimInfoOf<T>, u64)>;
+		// The block at the end of which a claim expires, for claims created with a term.
+		ClaimExpiry get(claim_expiry): map Digest => Option<T::BlockNumber>;
+		// The digests due to expire at the end of each block, at positions 0 to `ExpirationCount`
+		// excluded. Entries of claims revoked or renewed since are left in place and skipped.
+		Expirations get(expiration): double_map T::BlockNumber, blake2_256(u32) => Option<Digest>;
+		ExpirationCount get(expiration_count): map T::BlockNumber => u32;
+		// The next entry of `Expirations` to handle, when some were left over by the previous block.
+		ExpirationCursor get(expiration_cursor): Option<(T::BlockNumber, u32)>;
+		// The metadata of claims created or updated with some.
+		ClaimMetadataOf get(claim_metadata): map Digest => Option<ClaimMetadata>;
+		// Digests of known illegal content, which can't be claimed. Set by `blacklist_digest`.
+		Blacklist get(is_blacklisted): map Digest => bool;
+		// Pending offers to transfer a claim, keyed by the offered digest.
+		ClaimOffers get(claim_offer): map Digest => Option<ClaimOffer<T::AccountId, T::BlockNumber>>;
+		// On-chain override of the `ClaimDeposit` configured in the runtime, set by `set_claim_deposit`.
+		ClaimDepositOverride get(claim_deposit_override): Option<BalanceOf<T>>;
+		// The version of the storage layout, see `STORAGE_VERSION`. Chains started before versioning
+		// was introduced read 0, new chains start at the current version.
+		pub StorageVersion get(storage_version) build(|_| STORAGE_VERSION): u32;
+		// Claims waiting to be migrated to the current storage layout by `on_initialize`, in the
+		// order they were queued, from `MigrationHead` included to `MigrationTail` excluded.
+		PendingMigration get(pending_migration): map u32 => Option<Digest>;
+		MigrationHead get(migration_head): u32;
+		MigrationTail get(migration_tail): u32;
+		// Whether `finish_migration` has been called. The storage version is bumped once the queue is empty.
+		MigrationFinishing get(migration_finishing): bool;
 	}
 }
 
@@ -40,26 +328,87 @@ decl_module! {
 		// this is needed only if you are using events in your module
 		fn deposit_event() = default;
 
+		/// The base deposit reserved for claims, as configured in the runtime.
+		const ClaimDeposit: BalanceOf<T> = T::ClaimDeposit::get();
+
+		/// The maximum length, in bytes, of a proof digest.
+		const MaxDigestLength: u32 = T::MaxDigestLength::get();
+
+		/// The maximum number of claims migrated to the current storage layout per block.
+		const MigrationBatchSize: u32 = T::MigrationBatchSize::get();
+
+		/// The maximum number of digests that can be claimed at once with `create_claims`.
+		const MaxBatchSize: u32 = T::MaxBatchSize::get();
+
+		/// The maximum number of claims an account can hold by default.
+		const MaxClaimsPerAccount: u32 = T::MaxClaimsPerAccount::get();
+
+		/// The maximum number of expiration entries handled per block.
+		const MaxExpirationsPerBlock: u32 = T::MaxExpirationsPerBlock::get();
+
+		/// The maximum encoded length, in bytes, of the metadata of a claim.
+		const MaxMetadataLength: u32 = T::MaxMetadataLength::get();
+
+		/// The deposit reserved for each byte of a stored claim.
+		const DepositPerByte: BalanceOf<T> = T::DepositPerByte::get();
+
+		// Migrate a batch of pending claims at the beginning of each block.
+		fn on_initialize(_n: T::BlockNumber) {
+			Self::migrate_pending_claims();
+		}
+
+		// Remove the claims expiring by the end of this block, up to `MaxExpirationsPerBlock`.
+		fn on_finalize(n: T::BlockNumber) {
+			Self::expire_claims(n);
+		}
+
 		// This function can be called by the external world as an extrinsics call.
 		// The origin parameter is of type `AccountId`.
 		// The function performs a few verifications, then stores the proof and emits an event.
-		fn create_claim(origin, digest: Vec<u8>) -> Result {
+		// With `expires_at`, the claim is removed and its deposit released at the end of that block.
+		// The deposit is the base claim deposit plus `DepositPerByte` for each byte stored.
+		#[weight = ClaimWeight(storage_weight(21, 13))]
+		fn create_claim(
+			origin,
+			digest: Digest,
+			expires_at: Option<T::BlockNumber>,
+			metadata: Option<ClaimMetadata>
+		) -> Result {
 			// Verify that the incoming transaction is signed
 			let sender = ensure_signed(origin)?;
 
-			// Validate digest does not exceed a maximum size
-			ensure!(digest.len() <= DIGEST_MAXSIZE, ERR_DIGEST_TOO_LONG);
+			// Validate digest size against the maximum size and its hash algorithm
+			Self::ensure_valid_digest(&digest)?;
 
 			// Verify that the specified proof has not been claimed yet
-			ensure!(!Proofs::<T>::exists(&digest), "This proof has already been claimed");
+			ensure!(!Self::is_claimed(&digest), "This proof has already been claimed");
+			ensure!(!Self::is_blacklisted(&digest), ERR_BLACKLISTED);
+
+			// Verify that the sender can hold one more claim
+			Self::ensure_claim_quota(&sender, 1)?;
+
+			if let Some(expires_at) = expires_at {
+				ensure!(expires_at > system::Module::<T>::block_number(), ERR_EXPIRY_IN_PAST);
+			}
+			Self::ensure_valid_metadata(metadata.as_ref())?;
+
 			// Get current time for current block using the base timestamp module
 			let time = timestamp::Module::<T>::now();
 
-			// Reserve the fee in the sender's account balance
-			T::Currency::reserve(&sender, BalanceOf::<T>::from(POE_FEE))?;
+			// Reserve the deposit in the sender's account balance, for the record as it will be stored
+			let mut claim = Self::new_claim_info(&sender, time.clone(), Zero::zero());
+			claim.deposit = Self::record_deposit(&digest, &claim, metadata.as_ref());
+			T::Currency::reserve(&sender, claim.deposit)?;
 
-			// Store the proof and the sender of the transaction, plus block time
-			Proofs::<T>::insert(&digest, (sender.clone(), time.clone()));
+			// Store the proof and the sender of the transaction, plus block time and position,
+			// and the deposit paid for this claim
+			Self::store_claim(&digest, claim);
+			if let Some(expires_at) = expires_at {
+				Self::schedule_expiry(&digest, expires_at);
+			}
+			if let Some(metadata) = metadata {
+				ClaimMetadataOf::insert(&digest, metadata);
+			}
 
 			// Issue an event to notify that the proof was successfully claimed
 			Self::deposit_event(RawEvent::ClaimCreated(sender, time, digest));
@@ -67,168 +416,1990 @@ decl_module! {
 			Ok(())
 		}
 
+		// Claim several digests at once, with a single reservation for all their deposits.
+		// Either all the digests are claimed, or none of them.
+		#[weight = WeightPerClaim(storage_weight(7, 2), storage_weight(12, 7))]
+		fn create_claims(origin, digests: Vec<Digest>) -> Result {
+			// Verify that the incoming transaction is signed
+			let sender = ensure_signed(origin)?;
+
+			ensure!(!digests.is_empty(), "No digest to claim");
+			ensure!(digests.len() <= T::MaxBatchSize::get() as usize, "Too many digests in batch");
+
+			// Verify every digest before modifying anything. The batch is bounded,
+			// so looking for duplicates by pairs is fine.
+			for (i, digest) in digests.iter().enumerate() {
+				Self::ensure_valid_digest(digest)?;
+				ensure!(!Self::is_claimed(digest), "This proof has already been claimed");
+				ensure!(!Self::is_blacklisted(digest), ERR_BLACKLISTED);
+				ensure!(!digests[..i].contains(digest), "Duplicate digest in batch");
+			}
+			Self::ensure_claim_quota(&sender, digests.len() as u32)?;
+			let time = timestamp::Module::<T>::now();
+
+			// Reserve the deposits of all the claims at once
+			let mut claims = Vec::with_capacity(digests.len());
+			let mut total: BalanceOf<T> = Zero::zero();
+			for digest in &digests {
+				let mut claim = Self::new_claim_info(&sender, time.clone(), Zero::zero());
+				claim.deposit = Self::record_deposit(digest, &claim, None);
+				total = total.saturating_add(claim.deposit);
+				claims.push(claim);
+			}
+			T::Currency::reserve(&sender, total)?;
+
+			for (digest, claim) in digests.into_iter().zip(claims) {
+				Self::store_claim(&digest, claim);
+				Self::deposit_event(RawEvent::ClaimCreated(sender.clone(), time.clone(), digest));
+			}
+
+			Ok(())
+		}
+
 		// This function's structure is similar to the store_proof function.
 		// The function performs a few verifications, then revoke an existing proof from storage,
 		// and finally emits an event.
-		fn revoke_claim(origin, digest: Vec<u8>) -> Result {
+		#[weight = ClaimWeight(storage_weight(14, 15))]
+		fn revoke_claim(origin, digest: Digest) -> Result {
 			// Verify that the incoming transaction is signed
 			let sender = ensure_signed(origin)?;
 
-			// Validate digest does not exceed a maximum size
-			ensure!(digest.len() <= DIGEST_MAXSIZE, ERR_DIGEST_TOO_LONG);
-
-			// Verify that the specified proof has been claimed before
-			ensure!(Proofs::<T>::exists(&digest), "This proof has not been claimed yet");
+			// Validate digest size against the maximum size and its hash algorithm
+			Self::ensure_valid_digest(&digest)?;
 
-			// Get owner associated with the proof
-			let (owner, _time) = Self::proofs(&digest);
+			// Verify that the specified proof has been claimed before, and get its record
+			let claim = Self::claim(&digest).ok_or("This proof has not been claimed yet")?;
 
 			// Verify that sender of the current tx is the proof owner
-			ensure!(sender == owner, "You must own this claim to revoke it");
+			ensure!(sender == claim.owner, "You must own this claim to revoke it");
 
-			// Erase proof from storage
-			Proofs::<T>::remove(&digest);
+			// Erase proof from storage, along with any pending offer
+			Self::remove_claim(&digest, &claim);
 
-			// Release previously reserved fee from owner's account balance
-			T::Currency::unreserve(&sender, BalanceOf::<T>::from(POE_FEE));
+			// Release previously reserved deposit from owner's account balance
+			T::Currency::unreserve(&sender, claim.deposit);
 
 			// Issue an event to notify that the claim was effectively revoked
 			Self::deposit_event(RawEvent::ClaimRevoked(sender, digest));
 
 			Ok(())
 		}
-	}
-}
 
-// This module's events.
-decl_event!(
-	pub enum Event<T> where
-		AccountId = <T as system::Trait>::AccountId,
-		Moment = <T as timestamp::Trait>::Moment
-	 {
-		// Event emitted when a proof has been successfully claimed
-		ClaimCreated(AccountId, Moment, Vec<u8>),
-		// Event emitted when a proof claim has been revoked
-		ClaimRevoked(AccountId, Vec<u8>),
-	}
-);
+		// Set, replace or remove with `None` the metadata of a claim. Only the deposit for the bytes
+		// of metadata changes, the base deposit being kept: the difference is reserved or released.
+		#[weight = ClaimWeight(storage_weight(12, 6))]
+		fn set_claim_metadata(origin, digest: Digest, metadata: Option<ClaimMetadata>) -> Result {
+			let sender = ensure_signed(origin)?;
 
-// tests for this module
-#[cfg(test)]
-mod tests {
-	use super::*;
+			Self::ensure_valid_digest(&digest)?;
+			let claim = Self::claim(&digest).ok_or("This proof has not been claimed yet")?;
+			ensure!(sender == claim.owner, "You must own this claim to change its metadata");
 
-	use runtime_io::with_externalities;
-	use primitives::{H256, Blake2Hasher};
-	use support::{impl_outer_origin, assert_ok, assert_noop, parameter_types};
-	use sr_primitives::{traits::{BlakeTwo256, IdentityLookup}, testing::Header};
-	use sr_primitives::weights::Weight;
-	use sr_primitives::Perbill;
+			Self::ensure_valid_metadata(metadata.as_ref())?;
+			let previous = Self::claim_metadata(&digest);
+			let deposit = claim.deposit
+				.saturating_sub(Self::bytes_deposit(Self::metadata_len(previous.as_ref())))
+				.saturating_add(Self::bytes_deposit(Self::metadata_len(metadata.as_ref())));
+			Self::adjust_deposit(&digest, claim, deposit)?;
 
-	impl_outer_origin! {
-		pub enum Origin for Test {}
-	}
+			match metadata {
+				Some(metadata) => ClaimMetadataOf::insert(&digest, metadata),
+				None => ClaimMetadataOf::remove(&digest),
+			}
 
-	// For testing the module, we construct most of a mock runtime. This means
-	// first constructing a configuration type (`Test`) which `impl`s each of the
-	// configuration traits of modules we want to use.
-	#[derive(Clone, Eq, PartialEq)]
-	pub struct Test;
-	parameter_types! {
-		pub const BlockHashCount: u64 = 250;
-		pub const MaximumBlockWeight: Weight = 1024;
-		pub const MaximumBlockLength: u32 = 2 * 1024;
-		pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
-	}
-	impl system::Trait for Test {
-		type Origin = Origin;
-		type Call = ();
-		type Index = u64;
-		type BlockNumber = u64;
-		type Hash = H256;
-		type Hashing = BlakeTwo256;
-		type AccountId = u64;
-		type Lookup = IdentityLookup<Self::AccountId>;
-		type Header = Header;
-		type WeightMultiplierUpdate = ();
-		type Event = ();
-		type BlockHashCount = BlockHashCount;
-		type MaximumBlockWeight = MaximumBlockWeight;
-		type MaximumBlockLength = MaximumBlockLength;
-		type AvailableBlockRatio = AvailableBlockRatio;
-		type Version = ();
-	}
-	impl balances::Trait for Test {
-		type Balance = u64;
-		type OnNewAccount = ();
-		type OnFreeBalanceZero = ();
-		type Event = ();
-		type TransactionPayment = ();
-		type TransferPayment = ();
-		type DustRemoval = ();
-		type ExistentialDeposit = ();
-		type TransferFee = ();
-		type CreationFee = ();
-		type TransactionBaseFee = ();
-		type TransactionByteFee = ();
-		type WeightToFee = ();
-	}
-	parameter_types! {
-		pub const MinimumPeriod: u64 = 5;
-	}
-	impl timestamp::Trait for Test {
-		type Moment = u64;
-		type OnTimestampSet = ();
-		type MinimumPeriod = MinimumPeriod;
-	}
-	impl Trait for Test {
-		type Event = ();
-		type Currency = balances::Module<Test>;
-	}
-	type Balances = balances::Module<Test>;
-	type POEModule = Module<Test>;
+			Self::deposit_event(RawEvent::ClaimMetadataChanged(sender, digest));
 
-	// This function basically just builds a genesis storage key/value store according to
-	// our desired mockup.
-	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
-		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
-		balances::GenesisConfig::<Test> {
-			balances: vec![(1, 10000), (2, 10000)],
-			vesting: vec![],
-		}.assimilate_storage(&mut t).unwrap();
-		t.into()
-	}
+			Ok(())
+		}
 
-	#[test]
-	fn it_works() {
-		with_externalities(&mut new_test_ext(), || {
+		// Change the term of an expiring claim. It can only be extended, to a later block yet to
+		// come or indefinitely with `None`.
+		#[weight = ClaimWeight(storage_weight(9, 5))]
+		fn renew_claim(origin, digest: Digest, expires_at: Option<T::BlockNumber>) -> Result {
+			let sender = ensure_signed(origin)?;
 
-			// Verify it's not possible to store exceedingly big digests (prevent DOS attack and/or chain storage bloat)
-			assert_noop!(POEModule::create_claim(Origin::signed(1), vec![0; 101]), "Digest too long (max 100 bytes)");
+			Self::ensure_valid_digest(&digest)?;
+			let claim = Self::claim(&digest).ok_or("This proof has not been claimed yet")?;
+			ensure!(sender == claim.owner, "You must own this claim to renew it");
+			let current = Self::claim_expiry(&digest).ok_or("This claim does not expire")?;
 
-			// Have account 1 create a claim
-			assert_ok!(POEModule::create_claim(Origin::signed(1), vec![0]));
+			match expires_at {
+				Some(expires_at) => {
+					ensure!(expires_at > current, "A renewal must extend the term of the claim");
+					ensure!(expires_at > system::Module::<T>::block_number(), ERR_EXPIRY_IN_PAST);
+					Self::schedule_expiry(&digest, expires_at);
+				},
+				None => ClaimExpiry::<T>::remove(&digest),
+			}
 
-			// Check that account 1 reserved their deposit for creating a claim
-			assert_eq!(Balances::free_balance(&1), 9000);
-			assert_eq!(Balances::reserved_balance(&1), 1000);
+			Self::deposit_event(RawEvent::ClaimRenewed(sender, digest, expires_at));
 
-			// Check that account 2 cannot create the same claim
-			assert_noop!(POEModule::create_claim(Origin::signed(2), vec![0]), "This proof has already been claimed");
-			// Check that account 2 cannot revoke a claim they do not own
-			assert_noop!(POEModule::revoke_claim(Origin::signed(2), vec![0]), "You must own this claim to revoke it");
-			// Check that account 2 cannot revoke some non-existent claim
-			assert_noop!(POEModule::revoke_claim(Origin::signed(2), vec![1]), "This proof has not been claimed yet");
+			Ok(())
+		}
 
-			// Check that account 1 can revoke their claim
-			assert_ok!(POEModule::revoke_claim(Origin::signed(1), vec![0]));
+		// Anchor the Merkle root of a batch of `leaf_count` documents with a single claim.
+		// The existence of each document can then be proven with `verify_inclusion`.
+		#[weight = SimpleDispatchInfo::FixedNormal(storage_weight(14, 6))]
+		fn create_anchor(origin, merkle_root: H256, leaf_count: u64) -> Result {
+			let sender = ensure_signed(origin)?;
 
-			// Check that account 1 got back their deposit
-			assert_eq!(Balances::free_balance(&1), 10000);
-			assert_eq!(Balances::reserved_balance(&1), 0);
+			ensure!(leaf_count > 0, "An anchor must have at least one leaf");
+			ensure!(!Anchors::<T>::exists(&merkle_root), "This root has already been anchored");
+			let time = timestamp::Module::<T>::now();
 
-			// Check that account 2 can now claim this digest
-			assert_ok!(POEModule::create_claim(Origin::signed(2), vec![0]));
-		});
+			// Reserve the deposit in the sender's account balance, for the record as it will be stored
+			let mut claim = Self::new_claim_info(&sender, time.clone(), Zero::zero());
+			claim.deposit = Self::anchor_deposit(&merkle_root, &claim, leaf_count);
+			T::Currency::reserve(&sender, claim.deposit)?;
+
+			Anchors::<T>::insert(&merkle_root, (claim, leaf_count));
+			AnchorCount::mutate(|count| *count += 1);
+
+			Self::deposit_event(RawEvent::AnchorCreated(sender, time, merkle_root, leaf_count));
+
+			Ok(())
+		}
+
+		// Remove an anchored Merkle root, releasing its deposit.
+		#[weight = SimpleDispatchInfo::FixedNormal(storage_weight(9, 6))]
+		fn revoke_anchor(origin, merkle_root: H256) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let (claim, _leaf_count) = Self::anchor(&merkle_root).ok_or("This root has not been anchored yet")?;
+			ensure!(sender == claim.owner, "You must own this anchor to revoke it");
+
+			Anchors::<T>::remove(&merkle_root);
+			AnchorCount::mutate(|count| *count = count.saturating_sub(1));
+			T::Currency::unreserve(&sender, claim.deposit);
+
+			Self::deposit_event(RawEvent::AnchorRevoked(sender, merkle_root));
+
+			Ok(())
+		}
+
+		// Transfer the ownership of a claim to another account, keeping its original timestamp.
+		// The new owner reserves the deposit of the claim, and the previous owner's deposit is released.
+		#[weight = ClaimWeight(storage_weight(20, 17))]
+		fn transfer_claim(origin, digest: Digest, new_owner: T::AccountId) -> Result {
+			// Verify that the incoming transaction is signed
+			let sender = ensure_signed(origin)?;
+
+			// Validate digest size against the maximum size and its hash algorithm
+			Self::ensure_valid_digest(&digest)?;
+
+			// Verify that the specified proof has been claimed before, and get its record
+			let claim = Self::claim(&digest).ok_or("This proof has not been claimed yet")?;
+
+			// Verify that sender of the current tx is the proof owner
+			ensure!(sender == claim.owner, "You must own this claim to transfer it");
+			ensure!(sender != new_owner, "You already own this claim");
+
+			Self::do_transfer(&digest, claim, new_owner)
+		}
+
+		// Offer a claim to another account, optionally for a limited number of blocks.
+		// Nothing is reserved from the recipient until it accepts the offer with `accept_claim`.
+		// A new offer replaces any pending one for the same claim.
+		#[weight = ClaimWeight(storage_weight(7, 3))]
+		fn offer_claim(origin, digest: Digest, recipient: T::AccountId, expires_in: Option<T::BlockNumber>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			Self::ensure_valid_digest(&digest)?;
+			let owner = Self::claim(&digest).ok_or("This proof has not been claimed yet")?.owner;
+			ensure!(sender == owner, "You must own this claim to offer it");
+			ensure!(sender != recipient, "You already own this claim");
+
+			let expires_at = expires_in.map(|blocks| system::Module::<T>::block_number().saturating_add(blocks));
+			ClaimOffers::<T>::insert(&digest, ClaimOffer { recipient: recipient.clone(), expires_at });
+
+			Self::deposit_event(RawEvent::ClaimOffered(owner, recipient, digest, expires_at));
+
+			Ok(())
+		}
+
+		// Accept a pending offer, becoming the owner of the claim and reserving its deposit.
+		#[weight = ClaimWeight(storage_weight(22, 17))]
+		fn accept_claim(origin, digest: Digest) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			Self::ensure_valid_digest(&digest)?;
+			let offer = Self::claim_offer(&digest).ok_or("This claim has not been offered")?;
+			ensure!(sender == offer.recipient, "This claim has not been offered to you");
+			if let Some(expires_at) = offer.expires_at {
+				ensure!(system::Module::<T>::block_number() <= expires_at, "This claim offer has expired");
+			}
+
+			let claim = Self::claim(&digest).ok_or("This proof has not been claimed yet")?;
+			Self::do_transfer(&digest, claim, sender)
+		}
+
+		// Withdraw a pending offer. Can be called by the owner of the claim or by the recipient
+		// of the offer, to decline it.
+		#[weight = ClaimWeight(storage_weight(7, 3))]
+		fn cancel_claim_offer(origin, digest: Digest) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			Self::ensure_valid_digest(&digest)?;
+			let offer = Self::claim_offer(&digest).ok_or("This claim has not been offered")?;
+			let owner = Self::claim(&digest).ok_or("This proof has not been claimed yet")?.owner;
+			ensure!(sender == owner || sender == offer.recipient, "You must own or be offered this claim to cancel it");
+
+			ClaimOffers::<T>::remove(&digest);
+
+			Self::deposit_event(RawEvent::ClaimOfferCancelled(owner, offer.recipient, digest));
+
+			Ok(())
+		}
+
+		// Remove an abusive or illegal claim. Must be called by the root origin (e.g. via sudo).
+		// The deposit of the owner is either released, or slashed and handed to `OnSlash`.
+		// The digest is not checked against the current bounds, so that any claim can be removed.
+		#[weight = SimpleDispatchInfo::FixedOperational(storage_weight(14, 15))]
+		fn force_remove_claim(origin, digest: Digest, slash: bool) -> Result {
+			ensure_root(origin)?;
+
+			let claim = Self::claim(&digest).ok_or("This proof has not been claimed yet")?;
+
+			Self::remove_claim(&digest, &claim);
+
+			let slashed = if slash {
+				let (imbalance, _not_slashed) = T::Currency::slash_reserved(&claim.owner, claim.deposit);
+				let slashed = imbalance.peek();
+				T::OnSlash::on_unbalanced(imbalance);
+				slashed
+			} else {
+				T::Currency::unreserve(&claim.owner, claim.deposit);
+				Zero::zero()
+			};
+
+			Self::deposit_event(RawEvent::ClaimForceRemoved(claim.owner, digest, slashed));
+
+			Ok(())
+		}
+
+		// Prevent a digest from being claimed. Must be called by the root origin (e.g. via sudo).
+		// An existing claim of the digest is kept, and can be removed with `force_remove_claim`.
+		#[weight = SimpleDispatchInfo::FixedOperational(storage_weight(4, 3))]
+		fn blacklist_digest(origin, digest: Digest) -> Result {
+			ensure_root(origin)?;
+
+			Blacklist::insert(&digest, true);
+
+			Self::deposit_event(RawEvent::DigestBlacklisted(digest));
+
+			Ok(())
+		}
+
+		// Allow a blacklisted digest to be claimed again. Must be called by the root origin.
+		#[weight = SimpleDispatchInfo::FixedOperational(storage_weight(5, 3))]
+		fn unblacklist_digest(origin, digest: Digest) -> Result {
+			ensure_root(origin)?;
+
+			ensure!(Self::is_blacklisted(&digest), "This digest is not blacklisted");
+			Blacklist::remove(&digest);
+
+			Self::deposit_event(RawEvent::DigestUnblacklisted(digest));
+
+			Ok(())
+		}
+
+		// Change the deposit reserved for new claims. Must be called by the root origin (e.g. via sudo).
+		// Existing claims keep the deposit they were created with.
+		#[weight = SimpleDispatchInfo::FixedOperational(storage_weight(4, 3))]
+		fn set_claim_deposit(origin, deposit: BalanceOf<T>) -> Result {
+			ensure_root(origin)?;
+
+			ClaimDepositOverride::<T>::put(deposit);
+
+			Self::deposit_event(RawEvent::ClaimDepositChanged(deposit));
+
+			Ok(())
+		}
+
+		// Grant an account a claim quota other than `MaxClaimsPerAccount`, or reset it to the default
+		// with `None`. Must be called by the root origin (e.g. via sudo). Claims already held above a
+		// lowered quota are kept, but no new ones can be created or received.
+		#[weight = SimpleDispatchInfo::FixedOperational(storage_weight(4, 3))]
+		fn set_claim_quota(origin, account: T::AccountId, quota: Option<u32>) -> Result {
+			ensure_root(origin)?;
+
+			match quota {
+				Some(quota) => ClaimQuotaOverride::<T>::insert(&account, quota),
+				None => ClaimQuotaOverride::<T>::remove(&account),
+			}
+
+			Self::deposit_event(RawEvent::ClaimQuotaChanged(account, quota));
+
+			Ok(())
+		}
+
+		// Queue claims still stored with the legacy `(AccountId, Moment)` layout for migration.
+		// Must be called by the root origin (e.g. via sudo), with every digest claimed before the
+		// upgrade (as found in `ClaimCreated` events, or `ProofStored` events for untagged digests,
+		// whose algorithm is inferred from their length unless given). They are migrated in batches
+		// of `MigrationBatchSize` per block. At most `MaxBatchSize` digests can be queued at once, so
+		// this can be called as many times as needed, before `finish_migration`.
+		#[weight = MigrationWeight(storage_weight(2, 1))]
+		fn migrate_claims(origin, digests: Vec<LegacyDigest>) -> Result {
+			ensure_root(origin)?;
+
+			ensure!(Self::storage_version() < STORAGE_VERSION, "Claims storage is already up to date");
+			ensure!(digests.len() <= T::MaxBatchSize::get() as usize, "Too many digests in batch");
+
+			let mut tail = Self::migration_tail();
+			for digest in digests {
+				PendingMigration::insert(tail, digest.into_digest());
+				tail += 1;
+			}
+			MigrationTail::put(tail);
+
+			Ok(())
+		}
+
+		// Bump the storage version once the claims queued by `migrate_claims` have been migrated.
+		// Must be called by the root origin once every legacy claim has been queued. Claims missed
+		// are still migrated when touched, so that their owner or root can remove them.
+		#[weight = SimpleDispatchInfo::FixedOperational(storage_weight(2, 1))]
+		fn finish_migration(origin) -> Result {
+			ensure_root(origin)?;
+
+			ensure!(Self::storage_version() < STORAGE_VERSION, "Claims storage is already up to date");
+			MigrationFinishing::put(true);
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The record of a claimed proof, if any, whichever layout it is stored with.
+	///
+	/// Until migrated, a claim keyed by its untagged digest bytes is found with any algorithm.
+	/// Legacy layouts are read whatever the storage version, for claims missed by the migration.
+	pub fn claim(digest: &Digest) -> Option<ClaimInfoOf<T>> {
+		match runtime_io::storage(&Self::proof_key(digest)) {
+			Some(raw) => ClaimInfoOf::<T>::decode(&mut &raw[..]).ok()
+				.or_else(|| Self::upgrade_legacy_claim(&raw, Self::claim_deposit_of(digest))),
+			None => Self::untagged_claim(&digest.bytes),
+		}
+	}
+
+	/// Whether a digest is claimed, whichever layout its claim is stored with.
+	pub fn is_claimed(digest: &Digest) -> bool {
+		Proofs::<T>::exists(digest) ||
+			runtime_io::exists_storage(&Self::untagged_key(UNTAGGED_PROOFS_PREFIX, &digest.bytes))
+	}
+
+	/// The digests claimed by an account, from position `start` and at most `count` of them.
+	/// Positions change as claims are removed, the last claim of the account taking the place of
+	/// the removed one.
+	pub fn claims_of(account: &T::AccountId, start: u32, count: u32) -> Vec<Digest> {
+		let end = rstd::cmp::min(Self::owned_claim_count(account), start.saturating_add(count));
+		(start..end).filter_map(|position| Self::owned_claim(account, &position)).collect()
+	}
+
+	/// Figures about the proofs recorded by the module.
+	pub fn stats() -> PoeStats {
+		PoeStats {
+			claims: Self::claim_count(),
+			anchors: Self::anchor_count(),
+		}
+	}
+
+	/// Whether `leaf` is one of the documents anchored with `root`, given the sibling hashes
+	/// of its inclusion proof.
+	pub fn verify_inclusion(root: &H256, leaf: &Digest, proof: &[H256]) -> bool {
+		match Self::anchor(root) {
+			Some((_claim, leaf_count)) =>
+				proof.len() <= merkle::max_proof_len(leaf_count) && merkle::verify_inclusion(root, leaf, proof),
+			None => false,
+		}
+	}
+
+	/// Rewrite a claim stored with the legacy layout using `ClaimInfo`, under `digest` for claims
+	/// keyed by their untagged digest bytes. Returns whether the claim needed to be migrated.
+	pub fn migrate_claim(digest: &Digest) -> bool {
+		let claim = match runtime_io::storage(&Self::proof_key(digest)) {
+			Some(raw) => {
+				if ClaimInfoOf::<T>::decode(&mut &raw[..]).is_ok() {
+					return false;
+				}
+				Self::upgrade_legacy_claim(&raw, Self::claim_deposit_of(digest))
+			},
+			None => Self::untagged_claim(&digest.bytes),
+		};
+		match claim {
+			Some(claim) => {
+				Self::index_claim(&claim.owner, digest);
+				ClaimCount::mutate(|count| *count += 1);
+				Proofs::<T>::insert(digest, claim);
+				ClaimDeposits::<T>::remove(digest);
+				runtime_io::clear_storage(&Self::untagged_key(UNTAGGED_PROOFS_PREFIX, &digest.bytes));
+				runtime_io::clear_storage(&Self::untagged_key(UNTAGGED_DEPOSITS_PREFIX, &digest.bytes));
+				true
+			},
+			None => false,
+		}
+	}
+
+	// Migrate up to `MigrationBatchSize` pending claims, and bump the storage version once none
+	// are left if `finish_migration` has been called.
+	fn migrate_pending_claims() {
+		if Self::storage_version() >= STORAGE_VERSION {
+			return;
+		}
+		let (head, tail) = (Self::migration_head(), Self::migration_tail());
+		if head == tail && !Self::migration_finishing() {
+			return;
+		}
+
+		let end = rstd::cmp::min(tail, head.saturating_add(T::MigrationBatchSize::get()));
+		for index in head..end {
+			if let Some(digest) = PendingMigration::take(index) {
+				Self::migrate_claim(&digest);
+			}
+		}
+		if end < tail {
+			MigrationHead::put(end);
+			return;
+		}
+
+		MigrationHead::kill();
+		MigrationTail::kill();
+		if Self::migration_finishing() {
+			MigrationFinishing::kill();
+			StorageVersion::put(STORAGE_VERSION);
+			Self::deposit_event(RawEvent::StorageMigrated(STORAGE_VERSION));
+		}
+	}
+
+	// Decode a claim stored with the legacy layout, with the deposit recorded for it if any.
+	// The block it was created in is not known, so it is recorded as the genesis block.
+	fn upgrade_legacy_claim(raw: &[u8], deposit: Option<BalanceOf<T>>) -> Option<ClaimInfoOf<T>> {
+		let (owner, moment) = LegacyClaimOf::<T>::decode(&mut &raw[..]).ok()?;
+		Some(ClaimInfo {
+			owner,
+			moment,
+			block_number: Zero::zero(),
+			extrinsic_index: 0,
+			deposit: deposit.unwrap_or_else(|| BalanceOf::<T>::from(LEGACY_CLAIM_DEPOSIT)),
+		})
+	}
+
+	// Read a claim stored with the legacy layout under its untagged digest bytes, as by chains
+	// started before `Digest` was introduced.
+	fn untagged_claim(bytes: &[u8]) -> Option<ClaimInfoOf<T>> {
+		let raw = runtime_io::storage(&Self::untagged_key(UNTAGGED_PROOFS_PREFIX, bytes))?;
+		let deposit = runtime_io::storage(&Self::untagged_key(UNTAGGED_DEPOSITS_PREFIX, bytes))
+			.and_then(|raw| BalanceOf::<T>::decode(&mut &raw[..]).ok());
+		Self::upgrade_legacy_claim(&raw, deposit)
+	}
+
+	// The storage key of a map entry keyed by untagged digest bytes.
+	fn untagged_key(prefix: &[u8], bytes: &[u8]) -> [u8; 32] {
+		let mut key = prefix.to_vec();
+		bytes.encode_to(&mut key);
+		runtime_io::blake2_256(&key)
+	}
+
+	/// The storage key of the `Proofs` entry for a digest, see `poe_primitives::proof_key`.
+	pub fn proof_key(digest: &Digest) -> [u8; 32] {
+		poe_primitives::proof_key(digest)
+	}
+
+	/// Check a call to this module before it enters the transaction pool, so that calls bound
+	/// to fail are rejected without taking block space. See `CheckPoeClaim`.
+	///
+	/// Transactions claiming digests provide a tag for each of them, so that only one pending
+	/// transaction can claim a given digest.
+	pub fn validate_call(who: &T::AccountId, call: &Call<T>) -> TransactionValidity {
+		let digests = match call {
+			Call::create_claim(digest, ..) => rstd::slice::from_ref(digest),
+			Call::create_claims(digests) => &digests[..],
+			Call::revoke_claim(digest) => {
+				if Self::claim(digest).map_or(true, |claim| claim.owner != *who) {
+					return InvalidTransaction::Custom(INVALID_NOT_OWNER).into();
+				}
+				return Ok(ValidTransaction::default());
+			},
+			_ => return Ok(ValidTransaction::default()),
+		};
+
+		let mut provides = Vec::with_capacity(digests.len());
+		for digest in digests {
+			if Self::is_blacklisted(digest) {
+				return InvalidTransaction::Custom(INVALID_BLACKLISTED).into();
+			}
+			if Self::is_claimed(digest) {
+				return InvalidTransaction::Custom(INVALID_ALREADY_CLAIMED).into();
+			}
+			provides.push(Self::claim_tag(digest));
+		}
+
+		Ok(ValidTransaction { provides, ..Default::default() })
+	}
+
+	/// The tag provided by transactions claiming a digest.
+	pub fn claim_tag(digest: &Digest) -> Vec<u8> {
+		(CLAIM_TAG_PREFIX, digest).encode()
+	}
+
+	/// The maximum number of claims an account can hold.
+	pub fn claim_quota(account: &T::AccountId) -> u32 {
+		Self::claim_quota_override(account).unwrap_or_else(T::MaxClaimsPerAccount::get)
+	}
+
+	/// Check that an account can hold `count` more claims.
+	pub fn ensure_claim_quota(account: &T::AccountId, count: u32) -> Result {
+		let owned = Self::owned_claim_count(account);
+		ensure!(owned.saturating_add(count) <= Self::claim_quota(account), ERR_QUOTA_EXCEEDED);
+		Ok(())
+	}
+
+	/// The deposit for storing a claim: the base claim deposit, plus `DepositPerByte` for each
+	/// byte of its encoded digest, record and metadata.
+	pub fn record_deposit(digest: &Digest, claim: &ClaimInfoOf<T>, metadata: Option<&ClaimMetadata>) -> BalanceOf<T> {
+		let len = digest.encode().len() + claim.encode().len() + Self::metadata_len(metadata);
+		Self::claim_deposit().saturating_add(Self::bytes_deposit(len))
+	}
+
+	/// The deposit for anchoring a Merkle root: the base claim deposit, plus `DepositPerByte` for
+	/// each byte of its encoded root, record and leaf count.
+	pub fn anchor_deposit(merkle_root: &H256, claim: &ClaimInfoOf<T>, leaf_count: u64) -> BalanceOf<T> {
+		let len = merkle_root.encode().len() + claim.encode().len() + leaf_count.encode().len();
+		Self::claim_deposit().saturating_add(Self::bytes_deposit(len))
+	}
+
+	/// Check that claim metadata is within `MaxMetadataLength` once encoded.
+	pub fn ensure_valid_metadata(metadata: Option<&ClaimMetadata>) -> Result {
+		ensure!(Self::metadata_len(metadata) <= T::MaxMetadataLength::get() as usize, ERR_METADATA_TOO_LONG);
+		Ok(())
+	}
+
+	// The deposit for storing `len` bytes.
+	fn bytes_deposit(len: usize) -> BalanceOf<T> {
+		T::DepositPerByte::get().saturating_mul(BalanceOf::<T>::from(len as u32))
+	}
+
+	// The encoded length of claim metadata, if any.
+	fn metadata_len(metadata: Option<&ClaimMetadata>) -> usize {
+		metadata.map_or(0, |metadata| metadata.encode().len())
+	}
+
+	// Change the deposit of a claim, reserving the difference from its owner or releasing the
+	// surplus. Nothing is modified if the difference can't be reserved.
+	fn adjust_deposit(digest: &Digest, claim: ClaimInfoOf<T>, deposit: BalanceOf<T>) -> Result {
+		if deposit > claim.deposit {
+			T::Currency::reserve(&claim.owner, deposit - claim.deposit)?;
+		} else {
+			T::Currency::unreserve(&claim.owner, claim.deposit - deposit);
+		}
+
+		// Legacy claims are rewritten with the current layout
+		Self::migrate_claim(digest);
+		Proofs::<T>::insert(digest, ClaimInfo { deposit, ..claim });
+
+		Ok(())
+	}
+
+	/// The base deposit currently reserved when creating a claim.
+	pub fn claim_deposit() -> BalanceOf<T> {
+		Self::claim_deposit_override().unwrap_or_else(T::ClaimDeposit::get)
+	}
+
+	// Record a new claim and index it under its owner.
+	fn store_claim(digest: &Digest, claim: ClaimInfoOf<T>) {
+		Self::index_claim(&claim.owner, digest);
+		Proofs::<T>::insert(digest, claim);
+		ClaimCount::mutate(|count| *count += 1);
+	}
+
+	// Erase a claim and everything attached to it. Its deposit is left to the caller.
+	fn remove_claim(digest: &Digest, claim: &ClaimInfoOf<T>) {
+		// Legacy claims are neither counted nor indexed until migrated
+		Self::migrate_claim(digest);
+		ClaimCount::mutate(|count| *count = count.saturating_sub(1));
+		Proofs::<T>::remove(digest);
+		ClaimDeposits::<T>::remove(digest);
+		ClaimOffers::<T>::remove(digest);
+		ClaimExpiry::<T>::remove(digest);
+		ClaimMetadataOf::remove(digest);
+		Self::unindex_claim(&claim.owner, digest);
+	}
+
+	// Set the block at the end of which a claim expires. Any previous entry in `Expirations`
+	// is skipped once reached.
+	fn schedule_expiry(digest: &Digest, expires_at: T::BlockNumber) {
+		ClaimExpiry::<T>::insert(digest, expires_at);
+		let position = Self::expiration_count(expires_at);
+		Expirations::<T>::insert(&expires_at, &position, digest.clone());
+		ExpirationCount::<T>::insert(expires_at, position + 1);
+	}
+
+	// Handle up to `MaxExpirationsPerBlock` entries of `Expirations` due by the end of block `n`,
+	// from `ExpirationCursor` if some were left over. Moving past a block whose entries have all
+	// been handled counts as one entry, so that a long backlog is caught up with in bounded steps.
+	fn expire_claims(n: T::BlockNumber) {
+		let (mut block, mut position) = match Self::expiration_cursor() {
+			Some(cursor) => cursor,
+			None if ExpirationCount::<T>::exists(n) => (n, 0),
+			None => return,
+		};
+
+		let mut count = Self::expiration_count(block);
+		for _ in 0..T::MaxExpirationsPerBlock::get() {
+			if position < count {
+				if let Some(digest) = Expirations::<T>::take(&block, &position) {
+					Self::expire_claim(n, digest);
+				}
+				position += 1;
+				continue;
+			}
+
+			ExpirationCount::<T>::remove(block);
+			if block >= n {
+				ExpirationCursor::<T>::kill();
+				return;
+			}
+			block = block.saturating_add(One::one());
+			position = 0;
+			count = Self::expiration_count(block);
+		}
+
+		ExpirationCursor::<T>::put((block, position));
+	}
+
+	// Remove a claim due to expire by the end of block `n`, releasing its deposit. Claims revoked
+	// or renewed since the entry was scheduled are skipped.
+	fn expire_claim(n: T::BlockNumber, digest: Digest) {
+		match Self::claim_expiry(&digest) {
+			Some(expires_at) if expires_at <= n => (),
+			_ => return,
+		}
+		if let Some(claim) = Self::claim(&digest) {
+			Self::remove_claim(&digest, &claim);
+			T::Currency::unreserve(&claim.owner, claim.deposit);
+			Self::deposit_event(RawEvent::ClaimExpired(claim.owner, digest));
+		}
+	}
+
+	// Add a digest to the claims of an account.
+	fn index_claim(owner: &T::AccountId, digest: &Digest) {
+		let count = Self::owned_claim_count(owner);
+		ClaimsByOwner::<T>::insert(owner, &count, digest.clone());
+		OwnedClaimPosition::insert(digest, count);
+		OwnedClaimCount::<T>::insert(owner, count + 1);
+	}
+
+	// Remove a digest from the claims of an account, moving its last claim to the freed position.
+	fn unindex_claim(owner: &T::AccountId, digest: &Digest) {
+		let position = match OwnedClaimPosition::take(digest) {
+			Some(position) => position,
+			None => return,
+		};
+		let last = Self::owned_claim_count(owner).saturating_sub(1);
+		if let Some(moved) = ClaimsByOwner::<T>::take(owner, &last) {
+			if position != last {
+				OwnedClaimPosition::insert(&moved, position);
+				ClaimsByOwner::<T>::insert(owner, &position, moved);
+			}
+		}
+		if last == 0 {
+			OwnedClaimCount::<T>::remove(owner);
+		} else {
+			OwnedClaimCount::<T>::insert(owner, last);
+		}
+	}
+
+	// The record of a claim created in the current block and extrinsic.
+	fn new_claim_info(owner: &T::AccountId, moment: T::Moment, deposit: BalanceOf<T>) -> ClaimInfoOf<T> {
+		ClaimInfo {
+			owner: owner.clone(),
+			moment,
+			block_number: system::Module::<T>::block_number(),
+			extrinsic_index: system::Module::<T>::extrinsic_index().unwrap_or_default(),
+			deposit,
+		}
+	}
+
+	// Move a claim to a new owner. The new owner's deposit is reserved before anything
+	// else is modified, so that a failure leaves storage untouched.
+	fn do_transfer(digest: &Digest, claim: ClaimInfoOf<T>, new_owner: T::AccountId) -> Result {
+		Self::ensure_claim_quota(&new_owner, 1)?;
+		T::Currency::reserve(&new_owner, claim.deposit)?;
+		T::Currency::unreserve(&claim.owner, claim.deposit);
+
+		// Legacy claims are indexed under their owner once migrated
+		Self::migrate_claim(digest);
+
+		let owner = claim.owner.clone();
+		Proofs::<T>::insert(digest, ClaimInfo { owner: new_owner.clone(), ..claim });
+		ClaimDeposits::<T>::remove(digest);
+		Self::unindex_claim(&owner, digest);
+		Self::index_claim(&new_owner, digest);
+		// A pending offer does not survive a change of ownership
+		ClaimOffers::<T>::remove(digest);
+
+		Self::deposit_event(RawEvent::ClaimTransferred(owner, new_owner, digest.clone()));
+
+		Ok(())
+	}
+
+	/// Check that a digest is within `MaxDigestLength` and has the length its algorithm produces.
+	pub fn ensure_valid_digest(digest: &Digest) -> Result {
+		ensure!(digest.bytes.len() <= T::MaxDigestLength::get() as usize, ERR_DIGEST_TOO_LONG);
+		ensure!(digest.bytes.len() == digest.algorithm.digest_len(), ERR_DIGEST_BAD_LENGTH);
+		Ok(())
+	}
+}
+
+/// Signed extension rejecting calls to this module that are bound to fail, before they enter
+/// the transaction pool: claims of blacklisted or already claimed digests, and revocations by
+/// someone else than the owner. See `Module::validate_call`.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckPoeClaim<T: Trait + Send + Sync>(PhantomData<T>);
+
+impl<T: Trait + Send + Sync> CheckPoeClaim<T> {
+	/// Create the extension.
+	pub fn new() -> Self {
+		CheckPoeClaim(PhantomData)
+	}
+}
+
+impl<T: Trait + Send + Sync> Default for CheckPoeClaim<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T: Trait + Send + Sync> rstd::fmt::Debug for CheckPoeClaim<T> {
+	fn fmt(&self, f: &mut rstd::fmt::Formatter) -> rstd::fmt::Result {
+		write!(f, "CheckPoeClaim")
+	}
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for CheckPoeClaim<T> where
+	<T as system::Trait>::Call: IsSubType<Module<T>, T>,
+{
+	type AccountId = T::AccountId;
+	type Call = <T as system::Trait>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> rstd::result::Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: DispatchInfo,
+		_len: usize,
+	) -> TransactionValidity {
+		match call.is_aux_sub_type() {
+			Some(call) => Module::<T>::validate_call(who, call),
+			None => Ok(ValidTransaction::default()),
+		}
+	}
+}
+
+// This module's events.
+decl_event!(
+	pub enum Event<T> where
+		AccountId = <T as system::Trait>::AccountId,
+		Moment = <T as timestamp::Trait>::Moment,
+		Balance = BalanceOf<T>,
+		BlockNumber = <T as system::Trait>::BlockNumber
+	 {
+		// Event emitted when a proof has been successfully claimed
+		ClaimCreated(AccountId, Moment, Digest),
+		// Event emitted when a proof claim has been revoked
+		ClaimRevoked(AccountId, Digest),
+		// Event emitted when the Merkle root of a batch of documents has been anchored
+		AnchorCreated(AccountId, Moment, H256, u64),
+		// Event emitted when an anchored Merkle root has been revoked
+		AnchorRevoked(AccountId, H256),
+		// Event emitted when a digest has been blacklisted by root
+		DigestBlacklisted(Digest),
+		// Event emitted when a digest has been removed from the blacklist by root
+		DigestUnblacklisted(Digest),
+		// Event emitted when root has removed a proof claim, with the amount slashed from its owner
+		ClaimForceRemoved(AccountId, Digest, Balance),
+		// Event emitted when the owner of a proof claim has changed its metadata
+		ClaimMetadataChanged(AccountId, Digest),
+		// Event emitted when a proof claim has been removed at the end of its term
+		ClaimExpired(AccountId, Digest),
+		// Event emitted when the term of a proof claim has been extended, `None` meaning indefinitely
+		ClaimRenewed(AccountId, Digest, Option<BlockNumber>),
+		// Event emitted when a proof claim has been transferred from an owner to another
+		ClaimTransferred(AccountId, AccountId, Digest),
+		// Event emitted when an owner offers a proof claim to another account, until an optional block
+		ClaimOffered(AccountId, AccountId, Digest, Option<BlockNumber>),
+		// Event emitted when a pending claim offer has been withdrawn or declined
+		ClaimOfferCancelled(AccountId, AccountId, Digest),
+		// Event emitted when the deposit for new claims has been changed
+		ClaimDepositChanged(Balance),
+		// Event emitted when the claim quota of an account is changed, `None` meaning the default
+		ClaimQuotaChanged(AccountId, Option<u32>),
+		// Event emitted when all claims have been migrated to the given storage version
+		StorageMigrated(u32),
+	}
+);
+
+// tests for this module
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use runtime_io::with_externalities;
+	use primitives::{H256, Blake2Hasher};
+	use support::{impl_outer_origin, assert_ok, assert_noop, parameter_types};
+	use sr_primitives::traits::{OnInitialize, OnFinalize};
+	use sr_primitives::{traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+	use sr_primitives::weights::{Weight, GetDispatchInfo};
+	use sr_primitives::Perbill;
+	use std::cell::RefCell;
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	// For testing the module, we construct most of a mock runtime. This means
+	// first constructing a configuration type (`Test`) which `impl`s each of the
+	// configuration traits of modules we want to use.
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Call = ();
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type WeightMultiplierUpdate = ();
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnNewAccount = ();
+		type OnFreeBalanceZero = ();
+		type Event = ();
+		type TransactionPayment = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ();
+		type TransferFee = ();
+		type CreationFee = ();
+		type TransactionBaseFee = ();
+		type TransactionByteFee = ();
+		type WeightToFee = ();
+	}
+	parameter_types! {
+		pub const MinimumPeriod: u64 = 5;
+	}
+	impl timestamp::Trait for Test {
+		type Moment = u64;
+		type OnTimestampSet = ();
+		type MinimumPeriod = MinimumPeriod;
+	}
+	parameter_types! {
+		pub const ClaimDeposit: u64 = 1000;
+		// Only 256-bit digests are accepted on the test chain
+		pub const MaxDigestLength: u32 = 32;
+		pub const MigrationBatchSize: u32 = 2;
+		pub const MaxBatchSize: u32 = 3;
+		pub const MaxClaimsPerAccount: u32 = 5;
+		pub const MaxExpirationsPerBlock: u32 = 2;
+		pub const MaxMetadataLength: u32 = 64;
+	}
+
+	thread_local! {
+		static DEPOSIT_PER_BYTE: RefCell<u64> = RefCell::new(0);
+	}
+
+	// Free by default, so that most tests only deal with the base deposit.
+	pub struct DepositPerByte;
+	impl Get<u64> for DepositPerByte {
+		fn get() -> u64 {
+			DEPOSIT_PER_BYTE.with(|v| *v.borrow())
+		}
+	}
+
+	// Charge storage by the byte in the current test.
+	fn set_deposit_per_byte(deposit: u64) {
+		DEPOSIT_PER_BYTE.with(|v| *v.borrow_mut() = deposit);
+	}
+
+	thread_local! {
+		static SLASHED: RefCell<u64> = RefCell::new(0);
+	}
+
+	// Record the total slashed from deposits, which are then burnt.
+	pub struct SlashRecorder;
+	impl OnUnbalanced<NegativeImbalanceOf<Test>> for SlashRecorder {
+		fn on_unbalanced(amount: NegativeImbalanceOf<Test>) {
+			SLASHED.with(|v| *v.borrow_mut() += amount.peek());
+		}
+	}
+
+	// The encoded length of a SHA-256 digest and of the record of its claim in the tests.
+	const STORED_CLAIM_LEN: u64 = 34 + 36;
+	// The encoded length of a Merkle root, of the record of its anchor and of its leaf count.
+	const STORED_ANCHOR_LEN: u64 = 32 + 36 + 8;
+	impl Trait for Test {
+		type Event = ();
+		type Currency = balances::Module<Test>;
+		type ClaimDeposit = ClaimDeposit;
+		type MaxDigestLength = MaxDigestLength;
+		type MigrationBatchSize = MigrationBatchSize;
+		type MaxBatchSize = MaxBatchSize;
+		type MaxClaimsPerAccount = MaxClaimsPerAccount;
+		type MaxExpirationsPerBlock = MaxExpirationsPerBlock;
+		type MaxMetadataLength = MaxMetadataLength;
+		type DepositPerByte = DepositPerByte;
+		type OnSlash = SlashRecorder;
+	}
+	type Balances = balances::Module<Test>;
+	type POEModule = Module<Test>;
+
+	// This function basically just builds a genesis storage key/value store according to
+	// our desired mockup.
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: vec![(1, 10000), (2, 10000), (3, 500)],
+			vesting: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+		GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
+		t.into()
+	}
+
+	// Build a SHA-256 digest filled with the given byte.
+	fn sha256(byte: u8) -> Digest {
+		Digest { algorithm: HashAlgorithm::Sha256, bytes: vec![byte; 32] }
+	}
+
+	// Roll the chain back to the unversioned storage layout, and store a claim with the legacy
+	// `(AccountId, Moment)` layout, its deposit being reserved and recorded separately.
+	fn seed_legacy_claim(digest: &Digest, owner: u64, moment: u64, deposit: u64) {
+		StorageVersion::put(0);
+		runtime_io::set_storage(&POEModule::proof_key(digest), &(owner, moment).encode());
+		ClaimDeposits::<Test>::insert(digest, deposit);
+		assert_ok!(<Balances as ReservableCurrency<_>>::reserve(&owner, deposit));
+	}
+
+	// The storage key of a map entry keyed by untagged digest bytes, as in the original layout.
+	fn untagged_key(prefix: &[u8], bytes: &[u8]) -> [u8; 32] {
+		let mut key = prefix.to_vec();
+		key.extend(bytes.to_vec().encode());
+		runtime_io::blake2_256(&key)
+	}
+
+	// Roll the chain back to the original storage layout, and store a claim keyed by its untagged
+	// digest bytes. Claims created before deposits were recorded reserved the legacy deposit.
+	fn seed_untagged_claim(bytes: &[u8], owner: u64, moment: u64, deposit: Option<u64>) {
+		StorageVersion::put(0);
+		runtime_io::set_storage(&untagged_key(b"PoeStorage Proofs", bytes), &(owner, moment).encode());
+		if let Some(deposit) = deposit {
+			runtime_io::set_storage(&untagged_key(b"PoeStorage ClaimDeposits", bytes), &deposit.encode());
+		}
+		let reserved = deposit.unwrap_or(LEGACY_CLAIM_DEPOSIT as u64);
+		assert_ok!(<Balances as ReservableCurrency<_>>::reserve(&owner, reserved));
+	}
+
+	// All the digests claimed by an account.
+	fn claims_of(account: u64) -> Vec<Digest> {
+		POEModule::claims_of(&account, 0, u32::max_value())
+	}
+
+	#[test]
+	fn it_works() {
+		with_externalities(&mut new_test_ext(), || {
+
+			// Verify it's not possible to store exceedingly big digests (prevent DOS attack and/or chain storage bloat)
+			let sha3_512 = Digest { algorithm: HashAlgorithm::Sha3_512, bytes: vec![0; 64] };
+			assert_noop!(POEModule::create_claim(Origin::signed(1), sha3_512, None, None), ERR_DIGEST_TOO_LONG);
+
+			// Have account 1 create a claim
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+
+			// Check that account 1 reserved their deposit for creating a claim
+			assert_eq!(Balances::free_balance(&1), 9000);
+			assert_eq!(Balances::reserved_balance(&1), 1000);
+
+			// Check that account 2 cannot create the same claim
+			assert_noop!(
+				POEModule::create_claim(Origin::signed(2), sha256(0), None, None),
+				"This proof has already been claimed"
+			);
+			// Check that account 2 cannot revoke a claim they do not own
+			assert_noop!(POEModule::revoke_claim(Origin::signed(2), sha256(0)), "You must own this claim to revoke it");
+			// Check that account 2 cannot revoke some non-existent claim
+			assert_noop!(POEModule::revoke_claim(Origin::signed(2), sha256(1)), "This proof has not been claimed yet");
+
+			// Check that account 1 can revoke their claim
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+
+			// Check that account 1 got back their deposit
+			assert_eq!(Balances::free_balance(&1), 10000);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+
+			// Check that account 2 can now claim this digest
+			assert_ok!(POEModule::create_claim(Origin::signed(2), sha256(0), None, None));
+		});
+	}
+
+	#[test]
+	fn claim_deposit_can_be_changed_by_root() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_eq!(POEModule::claim_deposit(), 1000);
+
+			// Have account 1 create a claim under the configured deposit
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+			assert_eq!(POEModule::claim(&sha256(0)).unwrap().deposit, 1000);
+
+			// Only root can change the deposit
+			assert!(POEModule::set_claim_deposit(Origin::signed(1), 500).is_err());
+			assert_ok!(POEModule::set_claim_deposit(system::RawOrigin::Root.into(), 500));
+			assert_eq!(POEModule::claim_deposit(), 500);
+
+			// New claims reserve the new deposit
+			assert_ok!(POEModule::create_claim(Origin::signed(2), sha256(1), None, None));
+			assert_eq!(Balances::reserved_balance(&2), 500);
+
+			// Revoking the older claim releases exactly what was reserved for it
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_eq!(Balances::free_balance(&1), 10000);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(POEModule::claim(&sha256(0)), None);
+		});
+	}
+
+	#[test]
+	fn digest_length_is_bounded_by_configured_maximum() {
+		with_externalities(&mut new_test_ext(), || {
+			let max = MaxDigestLength::get() as usize;
+
+			let digest = |len| Digest { algorithm: HashAlgorithm::Sha256, bytes: vec![1; len] };
+
+			// A digest of exactly the maximum length is accepted
+			assert_ok!(POEModule::create_claim(Origin::signed(1), digest(max), None, None));
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), digest(max)));
+
+			// One byte more is rejected, for both claiming and revoking
+			assert_noop!(POEModule::create_claim(Origin::signed(1), digest(max + 1), None, None), ERR_DIGEST_TOO_LONG);
+			assert_noop!(POEModule::revoke_claim(Origin::signed(1), digest(max + 1)), ERR_DIGEST_TOO_LONG);
+		});
+	}
+
+	#[test]
+	fn digest_length_must_match_algorithm() {
+		with_externalities(&mut new_test_ext(), || {
+			// A truncated SHA-256 digest is rejected
+			let truncated = Digest { algorithm: HashAlgorithm::Sha256, bytes: vec![0; 31] };
+			assert_noop!(POEModule::create_claim(Origin::signed(1), truncated, None, None), ERR_DIGEST_BAD_LENGTH);
+
+			// The same bytes computed with different algorithms are distinct claims
+			let blake2 = Digest { algorithm: HashAlgorithm::Blake2b256, bytes: vec![0; 32] };
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+			assert_ok!(POEModule::create_claim(Origin::signed(2), blake2.clone(), None, None));
+			assert_eq!(POEModule::claim(&blake2).unwrap().owner, 2);
+		});
+	}
+
+	#[test]
+	fn claim_can_be_transferred() {
+		with_externalities(&mut new_test_ext(), || {
+			timestamp::Module::<Test>::set_timestamp(42);
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+			timestamp::Module::<Test>::set_timestamp(84);
+
+			// Only the owner can transfer a claim, and only to someone else
+			assert_noop!(
+				POEModule::transfer_claim(Origin::signed(2), sha256(0), 2),
+				"You must own this claim to transfer it"
+			);
+			assert_noop!(POEModule::transfer_claim(Origin::signed(1), sha256(0), 1), "You already own this claim");
+			assert_noop!(
+				POEModule::transfer_claim(Origin::signed(1), sha256(1), 2),
+				"This proof has not been claimed yet"
+			);
+
+			// The new owner must be able to afford the deposit
+			assert!(POEModule::transfer_claim(Origin::signed(1), sha256(0), 3).is_err());
+			assert_eq!(POEModule::claim(&sha256(0)).map(|c| (c.owner, c.moment)), Some((1, 42)));
+
+			assert_ok!(POEModule::transfer_claim(Origin::signed(1), sha256(0), 2));
+
+			// Ownership moved, the original timestamp is preserved and the deposit followed the claim
+			assert_eq!(POEModule::claim(&sha256(0)).map(|c| (c.owner, c.moment)), Some((2, 42)));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 10000);
+			assert_eq!(Balances::reserved_balance(&2), 1000);
+
+			// The previous owner can no longer revoke it, the new one can
+			assert_noop!(
+				POEModule::revoke_claim(Origin::signed(1), sha256(0)),
+				"You must own this claim to revoke it"
+			);
+			assert_ok!(POEModule::revoke_claim(Origin::signed(2), sha256(0)));
+			assert_eq!(Balances::reserved_balance(&2), 0);
+		});
+	}
+
+	#[test]
+	fn claim_offer_must_be_accepted() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+
+			// Only the owner can offer a claim
+			assert_noop!(
+				POEModule::offer_claim(Origin::signed(2), sha256(0), 2, None),
+				"You must own this claim to offer it"
+			);
+			assert_ok!(POEModule::offer_claim(Origin::signed(1), sha256(0), 2, None));
+
+			// Offering does not reserve anything from the recipient, nor move the claim
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert_eq!(POEModule::claim(&sha256(0)).unwrap().owner, 1);
+
+			// Only the recipient can accept the offer
+			assert_noop!(
+				POEModule::accept_claim(Origin::signed(3), sha256(0)),
+				"This claim has not been offered to you"
+			);
+			assert_ok!(POEModule::accept_claim(Origin::signed(2), sha256(0)));
+
+			assert_eq!(POEModule::claim(&sha256(0)).unwrap().owner, 2);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::reserved_balance(&2), 1000);
+			assert_eq!(POEModule::claim_offer(sha256(0)), None);
+
+			// The offer cannot be accepted twice
+			assert_noop!(POEModule::accept_claim(Origin::signed(2), sha256(0)), "This claim has not been offered");
+		});
+	}
+
+	#[test]
+	fn claim_offer_can_expire_or_be_cancelled() {
+		with_externalities(&mut new_test_ext(), || {
+			system::Module::<Test>::set_block_number(1);
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+			assert_ok!(POEModule::offer_claim(Origin::signed(1), sha256(0), 2, Some(10)));
+			assert_eq!(POEModule::claim_offer(sha256(0)), Some(ClaimOffer { recipient: 2, expires_at: Some(11) }));
+
+			// The offer can no longer be accepted once expired
+			system::Module::<Test>::set_block_number(12);
+			assert_noop!(POEModule::accept_claim(Origin::signed(2), sha256(0)), "This claim offer has expired");
+
+			// Strangers cannot cancel the offer, the recipient can decline it
+			assert_noop!(
+				POEModule::cancel_claim_offer(Origin::signed(3), sha256(0)),
+				"You must own or be offered this claim to cancel it"
+			);
+			assert_ok!(POEModule::cancel_claim_offer(Origin::signed(2), sha256(0)));
+			assert_eq!(POEModule::claim_offer(sha256(0)), None);
+
+			// Revoking a claim discards its pending offer
+			assert_ok!(POEModule::offer_claim(Origin::signed(1), sha256(0), 2, None));
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_eq!(POEModule::claim_offer(sha256(0)), None);
+		});
+	}
+
+	#[test]
+	fn claim_records_block_and_extrinsic_index() {
+		with_externalities(&mut new_test_ext(), || {
+			system::Module::<Test>::set_block_number(7);
+			system::Module::<Test>::set_extrinsic_index(3);
+			timestamp::Module::<Test>::set_timestamp(42);
+
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+			assert_eq!(POEModule::claim(&sha256(0)), Some(ClaimInfo {
+				owner: 1,
+				moment: 42,
+				block_number: 7,
+				extrinsic_index: 3,
+				deposit: 1000,
+			}));
+		});
+	}
+
+	#[test]
+	fn proof_key_is_the_key_of_the_stored_claim() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+
+			// Storage proofs are checked against this key, without the runtime
+			let stored = runtime_io::storage(&poe_primitives::proof_key(&sha256(0)));
+			assert_eq!(stored, Proofs::<Test>::get(sha256(0)).map(|claim| claim.encode()));
+			assert!(stored.is_some());
+		});
+	}
+
+	#[test]
+	fn legacy_claims_are_readable_and_migrated() {
+		with_externalities(&mut new_test_ext(), || {
+			seed_legacy_claim(&sha256(0), 1, 42, 500);
+
+			let expected = ClaimInfo { owner: 1, moment: 42, block_number: 0, extrinsic_index: 0, deposit: 500 };
+
+			// The legacy claim is readable and enforced before migration
+			assert_eq!(POEModule::claim(&sha256(0)), Some(expected.clone()));
+			assert_noop!(
+				POEModule::create_claim(Origin::signed(2), sha256(0), None, None),
+				"This proof has already been claimed"
+			);
+
+			// Only root can migrate claims, and migrating twice is harmless
+			assert!(POEModule::migrate_claims(Origin::signed(1), vec![sha256(0).into()]).is_err());
+			let digests = vec![sha256(0).into(), sha256(1).into()];
+			assert_ok!(POEModule::migrate_claims(system::RawOrigin::Root.into(), digests));
+			POEModule::on_initialize(1);
+			assert!(!POEModule::migrate_claim(&sha256(0)));
+
+			assert_eq!(Proofs::<Test>::get(sha256(0)), Some(expected));
+			assert_eq!(POEModule::claim_deposit_of(sha256(0)), None);
+
+			// Revoking releases the deposit recorded for the legacy claim
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 10000);
+		});
+	}
+
+	#[test]
+	fn legacy_claims_are_migrated_in_batches() {
+		with_externalities(&mut new_test_ext(), || {
+			// New chains start with the current layout
+			assert_eq!(POEModule::storage_version(), STORAGE_VERSION);
+			assert_noop!(
+				POEModule::migrate_claims(system::RawOrigin::Root.into(), vec![]),
+				"Claims storage is already up to date"
+			);
+			assert_noop!(
+				POEModule::finish_migration(system::RawOrigin::Root.into()),
+				"Claims storage is already up to date"
+			);
+
+			let digests: Vec<_> = (0..5).map(sha256).collect();
+			for (i, digest) in digests.iter().enumerate() {
+				seed_legacy_claim(digest, 1 + i as u64 % 2, i as u64, 100);
+			}
+			let legacy: Vec<_> = digests.iter().cloned().map(LegacyDigest::from).collect();
+
+			// Digests are queued at most `MaxBatchSize` at a time
+			assert_noop!(
+				POEModule::migrate_claims(system::RawOrigin::Root.into(), legacy[..4].to_vec()),
+				"Too many digests in batch"
+			);
+			assert_ok!(POEModule::migrate_claims(system::RawOrigin::Root.into(), legacy[..3].to_vec()));
+
+			// Nothing happens until the next block
+			let pending = || POEModule::migration_tail() - POEModule::migration_head();
+			assert_eq!(pending(), 3);
+			assert!(POEModule::claim_deposit_of(&digests[0]).is_some());
+
+			// Claims are migrated `MigrationBatchSize` at a time
+			POEModule::on_initialize(1);
+			assert_eq!(pending(), 1);
+			assert_eq!(POEModule::pending_migration(0), None);
+			assert_eq!(POEModule::pending_migration(2), Some(digests[2].clone()));
+			POEModule::on_initialize(2);
+			assert_eq!(pending(), 0);
+			assert_eq!(POEModule::claim_deposit_of(&digests[2]), None);
+
+			// An empty queue doesn't bump the version, so that more claims can be queued later
+			POEModule::on_initialize(3);
+			assert_eq!(POEModule::storage_version(), 0);
+			assert_ok!(POEModule::migrate_claims(system::RawOrigin::Root.into(), legacy[3..].to_vec()));
+
+			// The storage version is bumped once root finished queueing and the queue is empty
+			assert!(POEModule::finish_migration(Origin::signed(1)).is_err());
+			assert_ok!(POEModule::finish_migration(system::RawOrigin::Root.into()));
+			assert_eq!(POEModule::storage_version(), 0);
+			POEModule::on_initialize(4);
+			assert_eq!(pending(), 0);
+			assert!(!POEModule::migration_finishing());
+			assert_eq!(POEModule::storage_version(), STORAGE_VERSION);
+			for (i, digest) in digests.iter().enumerate() {
+				assert_eq!(Proofs::<Test>::get(digest), Some(ClaimInfo {
+					owner: 1 + i as u64 % 2,
+					moment: i as u64,
+					block_number: 0,
+					extrinsic_index: 0,
+					deposit: 100,
+				}));
+				assert_eq!(POEModule::claim_deposit_of(digest), None);
+			}
+		});
+	}
+
+	#[test]
+	fn untagged_claims_are_rekeyed_when_migrated() {
+		with_externalities(&mut new_test_ext(), || {
+			seed_untagged_claim(&[0; 32], 1, 42, Some(500));
+			seed_untagged_claim(&[1; 32], 2, 43, None);
+			seed_untagged_claim(&[2; 20], 1, 44, Some(500));
+			let blake2 = Digest { algorithm: HashAlgorithm::Blake2b256, bytes: vec![1; 32] };
+			let short = Digest { algorithm: HashAlgorithm::Sha256, bytes: vec![2; 20] };
+
+			// Until migrated, the claims are readable and enforced whatever the algorithm
+			let expected = ClaimInfo { owner: 1, moment: 42, block_number: 0, extrinsic_index: 0, deposit: 500 };
+			assert_eq!(POEModule::claim(&sha256(0)), Some(expected.clone()));
+			assert_eq!(POEModule::claim(&blake2).map(|claim| claim.deposit), Some(LEGACY_CLAIM_DEPOSIT as u64));
+			assert_noop!(
+				POEModule::create_claim(Origin::signed(3), sha256(1), None, None),
+				"This proof has already been claimed"
+			);
+			let already_claimed = Err(InvalidTransaction::Custom(INVALID_ALREADY_CLAIMED).into());
+			assert_eq!(POEModule::validate_call(&3, &Call::create_claim(blake2.clone(), None, None)), already_claimed);
+
+			// The algorithm is given by root, or inferred from the length of the digest
+			let untagged = |bytes| LegacyDigest { algorithm: None, bytes };
+			let digests = vec![untagged(vec![0; 32]), blake2.clone().into(), untagged(vec![2; 20])];
+			assert_ok!(POEModule::migrate_claims(system::RawOrigin::Root.into(), digests));
+			assert_ok!(POEModule::finish_migration(system::RawOrigin::Root.into()));
+			POEModule::on_initialize(1);
+			POEModule::on_initialize(2);
+			assert_eq!(POEModule::storage_version(), STORAGE_VERSION);
+
+			// The claims are re-keyed under their digest, and the untagged entries are removed
+			assert_eq!(Proofs::<Test>::get(sha256(0)), Some(expected));
+			assert_eq!(Proofs::<Test>::get(&blake2).map(|claim| claim.owner), Some(2));
+			assert_eq!(POEModule::claim(&sha256(1)), None);
+			assert_eq!(runtime_io::storage(&untagged_key(b"PoeStorage Proofs", &[0; 32])), None);
+			assert_eq!(runtime_io::storage(&untagged_key(b"PoeStorage ClaimDeposits", &[0; 32])), None);
+			assert_eq!(claims_of(2), vec![blake2.clone()]);
+			assert_eq!(POEModule::stats().claims, 3);
+
+			// Their owners can revoke them, releasing the deposits reserved before the upgrade
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_ok!(POEModule::revoke_claim(Origin::signed(2), blake2));
+			assert_eq!(Balances::reserved_balance(&2), 0);
+
+			// Digests matching no algorithm are kept, and can only be removed by root
+			assert_noop!(POEModule::revoke_claim(Origin::signed(1), short.clone()), ERR_DIGEST_BAD_LENGTH);
+			assert_ok!(POEModule::force_remove_claim(system::RawOrigin::Root.into(), short, false));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 10000);
+		});
+	}
+
+	#[test]
+	fn legacy_claims_missed_by_the_migration_can_be_removed() {
+		with_externalities(&mut new_test_ext(), || {
+			seed_legacy_claim(&sha256(0), 1, 42, 500);
+			seed_untagged_claim(&[1; 32], 2, 43, Some(500));
+
+			// Root finishes the migration without queueing the claims
+			assert_ok!(POEModule::finish_migration(system::RawOrigin::Root.into()));
+			POEModule::on_initialize(1);
+			assert_eq!(POEModule::storage_version(), STORAGE_VERSION);
+
+			// They are still readable and enforced
+			assert_eq!(POEModule::claim(&sha256(0)).map(|claim| claim.deposit), Some(500));
+			assert_eq!(POEModule::claim(&sha256(1)).map(|claim| claim.owner), Some(2));
+			assert_noop!(
+				POEModule::create_claim(Origin::signed(3), sha256(1), None, None),
+				"This proof has already been claimed"
+			);
+
+			// Their owner or root can remove them, releasing the deposits reserved before the upgrade
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_ok!(POEModule::force_remove_claim(system::RawOrigin::Root.into(), sha256(1), false));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert_eq!(POEModule::claim(&sha256(1)), None);
+			assert_eq!(runtime_io::storage(&untagged_key(b"PoeStorage Proofs", &[1; 32])), None);
+			assert_eq!(POEModule::stats().claims, 0);
+		});
+	}
+
+	#[test]
+	fn claims_can_be_created_in_batch() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(POEModule::create_claims(Origin::signed(1), vec![]), "No digest to claim");
+			assert_noop!(
+				POEModule::create_claims(Origin::signed(1), (0..4).map(sha256).collect()),
+				"Too many digests in batch"
+			);
+			assert_noop!(
+				POEModule::create_claims(Origin::signed(1), vec![sha256(0), sha256(1), sha256(0)]),
+				"Duplicate digest in batch"
+			);
+
+			// A single claimed digest makes the whole batch fail
+			assert_ok!(POEModule::create_claim(Origin::signed(2), sha256(2), None, None));
+			assert_noop!(
+				POEModule::create_claims(Origin::signed(1), vec![sha256(0), sha256(1), sha256(2)]),
+				"This proof has already been claimed"
+			);
+			assert_eq!(POEModule::claim(&sha256(0)), None);
+
+			// The whole batch is claimed, with one deposit per claim
+			assert_ok!(POEModule::create_claims(Origin::signed(1), vec![sha256(0), sha256(1), sha256(3)]));
+			assert_eq!(Balances::reserved_balance(&1), 3000);
+			for byte in &[0, 1, 3] {
+				assert_eq!(POEModule::claim(&sha256(*byte)).map(|c| (c.owner, c.deposit)), Some((1, 1000)));
+			}
+
+			// Claims of a batch are revoked individually
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(1)));
+			assert_eq!(Balances::reserved_balance(&1), 2000);
+		});
+	}
+
+	#[test]
+	fn batch_weight_scales_with_batch_length() {
+		let weight = |n| WeightPerClaim(1_000, 10_000).weigh_data((&(0..n).map(sha256).collect::<Vec<_>>(),));
+		assert_eq!(weight(1), 1_000 + 10_000 + 32 * BYTE_WEIGHT);
+		assert_eq!(weight(3), 1_000 + 3 * (weight(1) - 1_000));
+	}
+
+	#[test]
+	fn every_call_has_an_explicit_weight() {
+		let default = SimpleDispatchInfo::default().weigh_data(());
+		let calls: Vec<Call<Test>> = vec![
+			Call::create_claim(sha256(0), None, None),
+			Call::create_claims(vec![sha256(0)]),
+			Call::revoke_claim(sha256(0)),
+			Call::set_claim_metadata(sha256(0), None),
+			Call::renew_claim(sha256(0), None),
+			Call::create_anchor(H256::zero(), 1),
+			Call::revoke_anchor(H256::zero()),
+			Call::transfer_claim(sha256(0), 2),
+			Call::offer_claim(sha256(0), 2, None),
+			Call::accept_claim(sha256(0)),
+			Call::cancel_claim_offer(sha256(0)),
+			Call::force_remove_claim(sha256(0), true),
+			Call::blacklist_digest(sha256(0)),
+			Call::unblacklist_digest(sha256(0)),
+			Call::set_claim_deposit(500),
+			Call::set_claim_quota(1, None),
+			Call::migrate_claims(vec![]),
+			Call::finish_migration(),
+		];
+
+		// Every dispatchable of the module is covered, the first encoded byte being the call index
+		let indices: std::collections::BTreeSet<_> = calls.iter().map(|call| call.encode()[0]).collect();
+		assert_eq!(indices.len(), Module::<Test>::call_functions().len());
+
+		for call in calls {
+			let info = call.get_dispatch_info();
+			assert_ne!(info.weight, default, "{:?} has the default weight", call);
+		}
+
+		// Root calls are operational, so that they fit in full blocks
+		let info = Call::<Test>::force_remove_claim(sha256(0), true).get_dispatch_info();
+		assert_eq!(info.class, DispatchClass::Operational);
+
+		// The weight follows the length of the digest and of the metadata
+		let sha3_512 = Digest { algorithm: HashAlgorithm::Sha3_512, bytes: vec![0; 64] };
+		let metadata = ClaimMetadata { uri: Some(vec![0; 20]), ..Default::default() };
+		let weight = |call: Call<Test>| call.get_dispatch_info().weight;
+		assert_eq!(
+			weight(Call::create_claim(sha3_512, None, None)),
+			weight(Call::create_claim(sha256(0), None, None)) + 32 * BYTE_WEIGHT
+		);
+		let bare = weight(Call::create_claim(sha256(0), None, None));
+		assert!(weight(Call::create_claim(sha256(0), None, Some(metadata))) > bare + 20 * BYTE_WEIGHT);
+	}
+
+	#[test]
+	fn documents_can_be_anchored_with_a_merkle_root() {
+		with_externalities(&mut new_test_ext(), || {
+			let documents: Vec<_> = (0..5).map(sha256).collect();
+			let tree = merkle::MerkleTree::new(&documents).unwrap();
+			let root = tree.root();
+
+			// Nothing is anchored yet
+			assert!(!POEModule::verify_inclusion(&root, &documents[0], &tree.proof(0).unwrap()));
+
+			assert_noop!(POEModule::create_anchor(Origin::signed(1), root, 0), "An anchor must have at least one leaf");
+			assert_ok!(POEModule::create_anchor(Origin::signed(1), root, tree.leaf_count()));
+			assert_eq!(Balances::reserved_balance(&1), 1000);
+			assert_noop!(
+				POEModule::create_anchor(Origin::signed(2), root, tree.leaf_count()),
+				"This root has already been anchored"
+			);
+
+			// Every document can be proven with a single on-chain entry
+			for (index, document) in documents.iter().enumerate() {
+				assert!(POEModule::verify_inclusion(&root, document, &tree.proof(index).unwrap()));
+			}
+			assert!(!POEModule::verify_inclusion(&root, &sha256(5), &tree.proof(0).unwrap()));
+
+			// Proofs longer than the anchored tree allows are rejected
+			let mut padded = tree.proof(0).unwrap();
+			padded.extend(vec![H256::zero(); 4]);
+			assert!(!POEModule::verify_inclusion(&root, &documents[0], &padded));
+
+			// Only the owner can revoke the anchor, releasing its deposit
+			assert_noop!(POEModule::revoke_anchor(Origin::signed(2), root), "You must own this anchor to revoke it");
+			assert_ok!(POEModule::revoke_anchor(Origin::signed(1), root));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert!(!POEModule::verify_inclusion(&root, &documents[0], &tree.proof(0).unwrap()));
+		});
+	}
+
+	#[test]
+	fn claims_are_listed_by_owner() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+			assert_ok!(POEModule::create_claims(Origin::signed(1), vec![sha256(1), sha256(2)]));
+			assert_ok!(POEModule::create_claim(Origin::signed(2), sha256(3), None, None));
+			assert_eq!(claims_of(1), vec![sha256(0), sha256(1), sha256(2)]);
+			assert_eq!(claims_of(2), vec![sha256(3)]);
+
+			assert_ok!(POEModule::transfer_claim(Origin::signed(1), sha256(1), 2));
+			assert_eq!(claims_of(1), vec![sha256(0), sha256(2)]);
+			assert_eq!(claims_of(2), vec![sha256(3), sha256(1)]);
+
+			// Claims are listed by pages, the last claim taking the place of a removed one
+			assert_eq!(POEModule::claims_of(&2, 1, 10), vec![sha256(1)]);
+			assert_eq!(POEModule::claims_of(&2, 0, 1), vec![sha256(3)]);
+			assert_eq!(POEModule::claims_of(&2, 2, 1), vec![]);
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_eq!(claims_of(1), vec![sha256(2)]);
+			assert_eq!(POEModule::owned_claim_position(sha256(2)), Some(0));
+
+			assert_ok!(POEModule::revoke_claim(Origin::signed(2), sha256(3)));
+			assert_ok!(POEModule::revoke_claim(Origin::signed(2), sha256(1)));
+			assert_eq!(claims_of(2), vec![]);
+			assert!(!ClaimsByOwner::<Test>::exists(&2, &0));
+			assert!(!OwnedClaimCount::<Test>::exists(2));
+		});
+	}
+
+	#[test]
+	fn stats_count_claims_and_anchors() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_eq!(POEModule::stats(), PoeStats::default());
+
+			assert_ok!(POEModule::create_claims(Origin::signed(1), vec![sha256(0), sha256(1)]));
+			assert_ok!(POEModule::create_anchor(Origin::signed(2), H256::repeat_byte(1), 4));
+			assert_ok!(POEModule::transfer_claim(Origin::signed(1), sha256(1), 2));
+			assert_eq!(POEModule::stats(), PoeStats { claims: 2, anchors: 1 });
+
+			// Legacy claims are counted once migrated, including when revoked or transferred first
+			seed_legacy_claim(&sha256(2), 1, 42, 100);
+			seed_legacy_claim(&sha256(3), 1, 42, 100);
+			assert_eq!(POEModule::stats().claims, 2);
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(2)));
+			assert_ok!(POEModule::transfer_claim(Origin::signed(1), sha256(3), 2));
+			assert_eq!(claims_of(2), vec![sha256(1), sha256(3)]);
+			assert_eq!(POEModule::stats().claims, 3);
+
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_ok!(POEModule::revoke_anchor(Origin::signed(2), H256::repeat_byte(1)));
+			assert_eq!(POEModule::stats(), PoeStats { claims: 2, anchors: 0 });
+		});
+	}
+
+	#[test]
+	fn claims_per_account_are_bounded_by_quota() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(POEModule::create_claims(Origin::signed(1), (0..3).map(sha256).collect()));
+			assert_noop!(POEModule::create_claims(Origin::signed(1), (3..6).map(sha256).collect()), ERR_QUOTA_EXCEEDED);
+			assert_ok!(POEModule::create_claims(Origin::signed(1), (3..5).map(sha256).collect()));
+			assert_eq!(POEModule::owned_claim_count(1), 5);
+			assert_noop!(POEModule::create_claim(Origin::signed(1), sha256(5), None, None), ERR_QUOTA_EXCEEDED);
+
+			// Receiving a claim counts against the quota of the new owner
+			assert_ok!(POEModule::create_claim(Origin::signed(2), sha256(5), None, None));
+			assert_noop!(POEModule::transfer_claim(Origin::signed(2), sha256(5), 1), ERR_QUOTA_EXCEEDED);
+			assert_ok!(POEModule::offer_claim(Origin::signed(2), sha256(5), 1, None));
+			assert_noop!(POEModule::accept_claim(Origin::signed(1), sha256(5)), ERR_QUOTA_EXCEEDED);
+
+			// Only root can grant a higher quota
+			assert!(POEModule::set_claim_quota(Origin::signed(1), 1, Some(6)).is_err());
+			assert_ok!(POEModule::set_claim_quota(system::RawOrigin::Root.into(), 1, Some(6)));
+			assert_eq!(POEModule::claim_quota(&1), 6);
+			assert_ok!(POEModule::accept_claim(Origin::signed(1), sha256(5)));
+			assert_eq!(POEModule::owned_claim_count(1), 6);
+			assert_eq!(POEModule::owned_claim_count(2), 0);
+
+			// Revoking frees a slot, and resetting the quota keeps the claims already held
+			assert_ok!(POEModule::set_claim_quota(system::RawOrigin::Root.into(), 1, None));
+			assert_eq!(POEModule::claim_quota(&1), 5);
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(1)));
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+			assert_eq!(POEModule::owned_claim_count(1), 5);
+		});
+	}
+
+	#[test]
+	fn claims_expire_in_bounded_batches() {
+		with_externalities(&mut new_test_ext(), || {
+			system::Module::<Test>::set_block_number(5);
+			assert_noop!(POEModule::create_claim(Origin::signed(1), sha256(0), Some(5), None), ERR_EXPIRY_IN_PAST);
+
+			for i in 0..3 {
+				assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(i), Some(10), None));
+			}
+			assert_ok!(POEModule::create_claim(Origin::signed(2), sha256(3), Some(10), None));
+			assert_ok!(POEModule::create_claim(Origin::signed(2), sha256(4), None, None));
+			assert_eq!(POEModule::claim_expiry(sha256(0)), Some(10));
+
+			// Revoked and renewed claims are skipped
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_noop!(
+				POEModule::renew_claim(Origin::signed(1), sha256(3), Some(20)),
+				"You must own this claim to renew it"
+			);
+			assert_noop!(
+				POEModule::renew_claim(Origin::signed(2), sha256(3), Some(10)),
+				"A renewal must extend the term of the claim"
+			);
+			assert_noop!(POEModule::renew_claim(Origin::signed(2), sha256(4), Some(20)), "This claim does not expire");
+			assert_ok!(POEModule::renew_claim(Origin::signed(2), sha256(3), Some(20)));
+
+			POEModule::on_finalize(9);
+			assert!(POEModule::claim(&sha256(1)).is_some());
+
+			// Two entries are handled per block, the others are left for the next blocks
+			POEModule::on_finalize(10);
+			assert_eq!(POEModule::claim(&sha256(1)), None);
+			assert!(POEModule::claim(&sha256(2)).is_some());
+			assert_eq!(POEModule::expiration_cursor(), Some((10, 2)));
+			assert_eq!(POEModule::expiration(&10, &2), Some(sha256(2)));
+
+			// A claim left past its term can't be renewed to a block already reached
+			system::Module::<Test>::set_block_number(11);
+			assert_noop!(POEModule::renew_claim(Origin::signed(1), sha256(2), Some(11)), ERR_EXPIRY_IN_PAST);
+
+			POEModule::on_finalize(11);
+			assert_eq!(POEModule::claim(&sha256(2)), None);
+			assert!(POEModule::claim(&sha256(3)).is_some());
+			assert_eq!(POEModule::expiration_cursor(), Some((10, 4)));
+
+			// Moving past blocks with nothing left counts against the bound as well
+			POEModule::on_finalize(12);
+			assert_eq!(POEModule::expiration_cursor(), Some((12, 0)));
+			POEModule::on_finalize(13);
+			assert_eq!(POEModule::expiration_cursor(), None);
+			assert_eq!(POEModule::expiration_count(10), 0);
+			assert_eq!(POEModule::expiration(&10, &0), None);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(claims_of(1), vec![]);
+			assert_eq!(POEModule::claim_expiry(sha256(1)), None);
+
+			// A renewal can also make a claim permanent
+			assert_ok!(POEModule::renew_claim(Origin::signed(2), sha256(3), None));
+			POEModule::on_finalize(20);
+			assert!(POEModule::claim(&sha256(3)).is_some());
+			assert_eq!(Balances::reserved_balance(&2), 2000);
+		});
+	}
+
+	#[test]
+	fn claim_metadata_is_covered_by_the_deposit() {
+		with_externalities(&mut new_test_ext(), || {
+			set_deposit_per_byte(10);
+			let metadata = ClaimMetadata {
+				name: Some(b"report.pdf".to_vec()),
+				content_type: Some(b"application/pdf".to_vec()),
+				size: Some(1024),
+				uri: None,
+			};
+			let len = metadata.encode().len() as u64;
+
+			let too_long = ClaimMetadata { uri: Some(vec![0; 64]), ..Default::default() };
+			assert_noop!(
+				POEModule::create_claim(Origin::signed(1), sha256(0), None, Some(too_long.clone())),
+				ERR_METADATA_TOO_LONG
+			);
+
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, Some(metadata.clone())));
+			assert_eq!(POEModule::claim_metadata(sha256(0)), Some(metadata));
+			assert_eq!(POEModule::claim(&sha256(0)).unwrap().deposit, 1000 + 10 * (STORED_CLAIM_LEN + len));
+			assert_eq!(Balances::reserved_balance(&1), 1000 + 10 * (STORED_CLAIM_LEN + len));
+
+			// Only the owner can change the metadata, and the deposit follows its length
+			let uri = ClaimMetadata { uri: Some(b"ipfs://QmHash".to_vec()), ..Default::default() };
+			let uri_len = uri.encode().len() as u64;
+			assert_noop!(
+				POEModule::set_claim_metadata(Origin::signed(2), sha256(0), Some(uri.clone())),
+				"You must own this claim to change its metadata"
+			);
+			assert_noop!(
+				POEModule::set_claim_metadata(Origin::signed(1), sha256(0), Some(too_long)),
+				ERR_METADATA_TOO_LONG
+			);
+			assert_ok!(POEModule::set_claim_metadata(Origin::signed(1), sha256(0), Some(uri.clone())));
+			assert_eq!(POEModule::claim_metadata(sha256(0)), Some(uri));
+			assert_eq!(Balances::reserved_balance(&1), 1000 + 10 * (STORED_CLAIM_LEN + uri_len));
+
+			assert_ok!(POEModule::set_claim_metadata(Origin::signed(1), sha256(0), None));
+			assert_eq!(POEModule::claim_metadata(sha256(0)), None);
+			assert_eq!(POEModule::claim(&sha256(0)).unwrap().deposit, 1000 + 10 * STORED_CLAIM_LEN);
+			assert_eq!(Balances::reserved_balance(&1), 1000 + 10 * STORED_CLAIM_LEN);
+
+			// Revoking releases the whole deposit and drops the metadata
+			let uri = ClaimMetadata { uri: Some(b"ipfs://QmHash".to_vec()), ..Default::default() };
+			assert_ok!(POEModule::set_claim_metadata(Origin::signed(1), sha256(0), Some(uri)));
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_eq!(POEModule::claim_metadata(sha256(0)), None);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 10000);
+		});
+	}
+
+	#[test]
+	fn deposit_is_proportional_to_stored_size() {
+		with_externalities(&mut new_test_ext(), || {
+			set_deposit_per_byte(10);
+
+			// Each claim pays for its digest and record, on top of the base deposit
+			assert_ok!(POEModule::create_claims(Origin::signed(1), vec![sha256(0), sha256(1)]));
+			assert_eq!(POEModule::claim(&sha256(0)).unwrap().deposit, 1000 + 10 * STORED_CLAIM_LEN);
+			assert_eq!(Balances::reserved_balance(&1), 2 * (1000 + 10 * STORED_CLAIM_LEN));
+
+			// Updates keep the base deposit the claim was created with, only the deposit for the
+			// metadata follows its length
+			assert_ok!(POEModule::set_claim_deposit(system::RawOrigin::Root.into(), 500));
+			let metadata = ClaimMetadata { size: Some(42), ..Default::default() };
+			assert_ok!(POEModule::set_claim_metadata(Origin::signed(1), sha256(0), Some(metadata.clone())));
+			let deposit = 1000 + 10 * (STORED_CLAIM_LEN + metadata.encode().len() as u64);
+			assert_eq!(POEModule::claim(&sha256(0)).unwrap().deposit, deposit);
+			assert_eq!(Balances::reserved_balance(&1), deposit + 1000 + 10 * STORED_CLAIM_LEN);
+			assert_ok!(POEModule::set_claim_metadata(Origin::signed(1), sha256(1), None));
+			assert_eq!(POEModule::claim(&sha256(1)).unwrap().deposit, 1000 + 10 * STORED_CLAIM_LEN);
+
+			// The difference must be affordable
+			set_deposit_per_byte(0);
+			assert_ok!(POEModule::create_claim(Origin::signed(3), sha256(2), None, None));
+			set_deposit_per_byte(10);
+			assert!(POEModule::set_claim_metadata(Origin::signed(3), sha256(2), Some(metadata)).is_err());
+			assert_eq!(POEModule::claim(&sha256(2)).unwrap().deposit, 500);
+			assert_eq!(POEModule::claim_metadata(sha256(2)), None);
+
+			// Revoking releases the whole deposit
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(0)));
+			assert_ok!(POEModule::revoke_claim(Origin::signed(1), sha256(1)));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 10000);
+		});
+	}
+
+	#[test]
+	fn anchor_deposit_is_proportional_to_stored_size() {
+		with_externalities(&mut new_test_ext(), || {
+			set_deposit_per_byte(10);
+			let root = H256::repeat_byte(1);
+
+			// An anchor pays for its root, record and leaf count, on top of the base deposit
+			assert_ok!(POEModule::create_anchor(Origin::signed(1), root, 5));
+			let (claim, _) = POEModule::anchor(&root).unwrap();
+			assert_eq!(claim.deposit, 1000 + 10 * STORED_ANCHOR_LEN);
+			assert_eq!(Balances::reserved_balance(&1), claim.deposit);
+
+			// The deposit must be affordable
+			set_deposit_per_byte(1000);
+			assert!(POEModule::create_anchor(Origin::signed(1), H256::repeat_byte(2), 5).is_err());
+			assert_eq!(POEModule::anchor(&H256::repeat_byte(2)), None);
+
+			// Revoking releases the whole deposit
+			assert_ok!(POEModule::revoke_anchor(Origin::signed(1), root));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 10000);
+		});
+	}
+
+	#[test]
+	fn claims_can_be_force_removed_by_root() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(POEModule::create_claims(Origin::signed(1), vec![sha256(0), sha256(1)]));
+			assert_ok!(POEModule::offer_claim(Origin::signed(1), sha256(0), 2, None));
+			let issuance = Balances::total_issuance();
+
+			assert!(POEModule::force_remove_claim(Origin::signed(2), sha256(0), true).is_err());
+			assert_noop!(
+				POEModule::force_remove_claim(system::RawOrigin::Root.into(), sha256(2), false),
+				"This proof has not been claimed yet"
+			);
+
+			// Without slashing, the deposit goes back to the owner
+			assert_ok!(POEModule::force_remove_claim(system::RawOrigin::Root.into(), sha256(1), false));
+			assert_eq!(POEModule::claim(&sha256(1)), None);
+			assert_eq!(Balances::reserved_balance(&1), 1000);
+			assert_eq!(Balances::free_balance(&1), 9000);
+
+			// Slashed deposits are handed to `OnSlash`
+			assert_ok!(POEModule::force_remove_claim(system::RawOrigin::Root.into(), sha256(0), true));
+			assert_eq!(POEModule::claim(&sha256(0)), None);
+			assert_eq!(POEModule::claim_offer(sha256(0)), None);
+			assert_eq!(claims_of(1), vec![]);
+			assert_eq!(POEModule::stats().claims, 0);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 9000);
+			assert_eq!(SLASHED.with(|v| *v.borrow()), 1000);
+			assert_eq!(Balances::total_issuance(), issuance - 1000);
+		});
+	}
+
+	#[test]
+	fn blacklisted_digests_cannot_be_claimed() {
+		with_externalities(&mut new_test_ext(), || {
+			assert!(POEModule::blacklist_digest(Origin::signed(1), sha256(0)).is_err());
+			assert_ok!(POEModule::blacklist_digest(system::RawOrigin::Root.into(), sha256(0)));
+			assert!(POEModule::is_blacklisted(sha256(0)));
+
+			assert_noop!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None), ERR_BLACKLISTED);
+			assert_noop!(POEModule::create_claims(Origin::signed(1), vec![sha256(1), sha256(0)]), ERR_BLACKLISTED);
+
+			// Blacklisted digests are rejected before entering the transaction pool
+			let rejected = Err(InvalidTransaction::Custom(INVALID_BLACKLISTED).into());
+			assert_eq!(POEModule::validate_call(&1, &Call::create_claim(sha256(0), None, None)), rejected);
+			assert_eq!(POEModule::validate_call(&1, &Call::create_claims(vec![sha256(1), sha256(0)])), rejected);
+			assert!(POEModule::validate_call(&1, &Call::create_claim(sha256(1), None, None)).is_ok());
+
+			assert!(POEModule::unblacklist_digest(Origin::signed(1), sha256(0)).is_err());
+			assert_ok!(POEModule::unblacklist_digest(system::RawOrigin::Root.into(), sha256(0)));
+			assert_noop!(
+				POEModule::unblacklist_digest(system::RawOrigin::Root.into(), sha256(0)),
+				"This digest is not blacklisted"
+			);
+			assert!(POEModule::validate_call(&1, &Call::create_claim(sha256(0), None, None)).is_ok());
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+		});
+	}
+
+	#[test]
+	fn doomed_calls_are_rejected_before_the_pool() {
+		with_externalities(&mut new_test_ext(), || {
+			// Claims provide a tag per digest, so that the pool keeps a single claim of each
+			let valid = POEModule::validate_call(&1, &Call::create_claim(sha256(0), None, None)).unwrap();
+			assert_eq!(valid.provides, vec![POEModule::claim_tag(&sha256(0))]);
+			let valid = POEModule::validate_call(&2, &Call::create_claims(vec![sha256(0), sha256(1)])).unwrap();
+			assert_eq!(valid.provides, vec![POEModule::claim_tag(&sha256(0)), POEModule::claim_tag(&sha256(1))]);
+			assert_ne!(POEModule::claim_tag(&sha256(0)), POEModule::claim_tag(&sha256(1)));
+
+			let already_claimed = Err(InvalidTransaction::Custom(INVALID_ALREADY_CLAIMED).into());
+			let not_owner = Err(InvalidTransaction::Custom(INVALID_NOT_OWNER).into());
+			assert_eq!(POEModule::validate_call(&1, &Call::revoke_claim(sha256(0))), not_owner);
+
+			assert_ok!(POEModule::create_claim(Origin::signed(1), sha256(0), None, None));
+			assert_eq!(POEModule::validate_call(&2, &Call::create_claim(sha256(0), None, None)), already_claimed);
+			assert_eq!(POEModule::validate_call(&2, &Call::create_claims(vec![sha256(1), sha256(0)])), already_claimed);
+			assert_eq!(POEModule::validate_call(&2, &Call::revoke_claim(sha256(0))), not_owner);
+			assert_eq!(POEModule::validate_call(&1, &Call::revoke_claim(sha256(0))), Ok(Default::default()));
+
+			// Other calls are left to dispatch
+			assert!(POEModule::validate_call(&2, &Call::transfer_claim(sha256(0), 3)).is_ok());
+		});
+	}
+
+	// Check that `Proofs` and `ClaimsByOwner` agree on who owns what, and that every account
+	// has exactly the deposits of its claims reserved. Legacy claims are only indexed once migrated.
+	fn assert_owner_index_consistent(digests: &[Digest], accounts: &[u64]) {
+		let mut migrated = 0;
+		for digest in digests {
+			let is_migrated = runtime_io::storage(&POEModule::proof_key(digest))
+				.map_or(false, |raw| ClaimInfoOf::<Test>::decode(&mut &raw[..]).is_ok());
+			let indexed = accounts.iter()
+				.map(|account| claims_of(*account).iter().filter(|d| *d == digest).count())
+				.sum::<usize>();
+			if is_migrated {
+				migrated += 1;
+				let owner = POEModule::claim(digest).unwrap().owner;
+				assert!(claims_of(owner).contains(digest));
+				assert_eq!(indexed, 1, "{:?} is indexed {} times", digest, indexed);
+			} else {
+				assert_eq!(indexed, 0, "{:?} is indexed without being claimed", digest);
+			}
+		}
+		assert_eq!(POEModule::stats().claims, migrated);
+
+		for account in accounts {
+			let deposits: u64 = digests.iter()
+				.filter_map(POEModule::claim)
+				.filter(|claim| claim.owner == *account)
+				.map(|claim| claim.deposit)
+				.sum();
+			// Positions are contiguous, and each digest knows its own
+			let claims = claims_of(*account);
+			assert_eq!(POEModule::owned_claim_count(account) as usize, claims.len());
+			assert!(!ClaimsByOwner::<Test>::exists(account, &(claims.len() as u32)));
+			for (position, digest) in claims.iter().enumerate() {
+				assert_eq!(POEModule::owned_claim_position(digest), Some(position as u32));
+			}
+			assert_eq!(Balances::reserved_balance(account), deposits);
+		}
+	}
+
+	#[test]
+	fn owner_index_stays_consistent_with_claims() {
+		let digests: Vec<_> = (0..6).map(sha256).collect();
+		let accounts = [1, 2, 3];
+
+		for seed in 1..=20u64 {
+			with_externalities(&mut new_test_ext(), || {
+				// A xorshift generator, so that failing sequences can be replayed from their seed
+				let mut state = seed;
+				let mut next = |bound: u64| {
+					state ^= state << 13;
+					state ^= state >> 7;
+					state ^= state << 17;
+					state % bound
+				};
+
+				// Some legacy claims, which get indexed once touched
+				seed_legacy_claim(&digests[4], 1, 42, 100);
+				seed_legacy_claim(&digests[5], 2, 42, 100);
+				// Metadata changes the deposit of a claim
+				set_deposit_per_byte(1);
+
+				for step in 0..50 {
+					system::Module::<Test>::set_block_number(step);
+					let sender = accounts[next(3) as usize];
+					let other = accounts[next(3) as usize];
+					let digest = digests[next(6) as usize].clone();
+
+					// Failing calls must leave the index untouched just as much as successful ones
+					let _ = match next(10) {
+						0 => POEModule::create_claim(Origin::signed(sender), digest, None, None),
+						1 => {
+							let digests = vec![digest, digests[next(6) as usize].clone()];
+							POEModule::create_claims(Origin::signed(sender), digests)
+						},
+						2 => POEModule::revoke_claim(Origin::signed(sender), digest),
+						3 => POEModule::transfer_claim(Origin::signed(sender), digest, other),
+						4 => POEModule::offer_claim(Origin::signed(sender), digest, other, Some(next(3))),
+						5 => POEModule::accept_claim(Origin::signed(sender), digest),
+						6 => POEModule::cancel_claim_offer(Origin::signed(sender), digest),
+						7 => POEModule::force_remove_claim(system::RawOrigin::Root.into(), digest, next(2) == 0),
+						8 => POEModule::create_claim(Origin::signed(sender), digest, Some(step + 1 + next(3)), None),
+						_ => {
+							let uri_len = next(20) as usize;
+							let metadata = ClaimMetadata { uri: Some(vec![0; uri_len]), ..Default::default() };
+							let metadata = Some(metadata).filter(|_| uri_len > 0);
+							POEModule::set_claim_metadata(Origin::signed(sender), digest, metadata)
+						},
+					};
+					// Claims created with a term expire at the end of their block
+					POEModule::on_finalize(step);
+					assert_owner_index_consistent(&digests, &accounts);
+				}
+			});
+		}
 	}
 }
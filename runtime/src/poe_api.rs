@@ -0,0 +1,32 @@
+//! Runtime APIs of the proof-of-existence module, used by the node and light clients to query
+//! the chain without decoding its storage.
+
+use rstd::vec::Vec;
+use primitives::H256;
+use codec::Codec;
+use client::decl_runtime_apis;
+use crate::poe::{Digest, ClaimInfo, PoeStats};
+
+decl_runtime_apis! {
+	/// Verification of documents anchored with a Merkle root.
+	pub trait AnchorApi {
+		/// Whether `leaf` is one of the documents anchored with `root`, given the sibling hashes
+		/// of its inclusion proof, as built by `merkle::MerkleTree`.
+		fn verify_inclusion(root: H256, leaf: Digest, proof: Vec<H256>) -> bool;
+	}
+
+	/// Queries of the claims recorded by the PoE module.
+	pub trait PoeApi<AccountId, Moment, BlockNumber, Balance> where
+		AccountId: Codec,
+		Moment: Codec,
+		BlockNumber: Codec,
+		Balance: Codec,
+	{
+		/// The record of a claimed digest, if any.
+		fn claim(digest: Digest) -> Option<ClaimInfo<AccountId, Moment, BlockNumber, Balance>>;
+		/// The digests claimed by an account, from position `start` and at most `count` of them.
+		fn claims_of(account: AccountId, start: u32, count: u32) -> Vec<Digest>;
+		/// Figures about the claims and anchors recorded.
+		fn stats() -> PoeStats;
+	}
+}
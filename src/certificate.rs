@@ -0,0 +1,270 @@
+//! Proof certificates: self-contained evidence that a digest is claimed, which can be checked
+//! offline by anyone trusting the GRANDPA authority set of the chain.
+//!
+//! A certificate holds the header of a finalized block, the GRANDPA justification finalizing it,
+//! and a storage read proof of the `Proofs` entry of the digest against the state root of the
+//! header. Checking it needs neither a node nor the rest of the chain.
+
+use std::collections::HashMap;
+use codec::{Decode, Encode};
+use finality_grandpa::{Commit, Message};
+use grandpa_primitives::{AuthorityId, AuthorityPair, AuthoritySignature, AuthorityWeight};
+use primitives::{Bytes, Pair};
+use serde::{Serialize, Deserialize};
+use sr_primitives::traits::Header as HeaderT;
+use substrate_poe_runtime::{BlockNumber, Hash, Header, poe::Digest};
+use crate::rpc::{ClaimJson, DigestJson};
+
+/// Evidence that a digest is claimed in the state of a finalized block.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Certificate {
+	/// The claimed digest.
+	pub digest: DigestJson,
+	/// Its claim, as recorded in the state of the block.
+	pub claim: ClaimJson,
+	/// The header of the block.
+	pub header: Header,
+	/// The trie nodes proving the `Proofs` entry of the digest against the state root of the block.
+	pub proof: Vec<Bytes>,
+	/// The encoded GRANDPA justification finalizing the block.
+	pub justification: Bytes,
+}
+
+/// The GRANDPA authorities trusted to finalize blocks.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthoritySet {
+	/// The id of the set, incremented on each change of authorities.
+	pub set_id: u64,
+	/// The authorities of the set, with their voting weight.
+	pub authorities: Vec<(AuthorityId, AuthorityWeight)>,
+}
+
+// Layout of the justifications stored by GRANDPA: the commit of a round, and the headers linking
+// the targets of its precommits to the committed block.
+#[derive(Encode, Decode)]
+struct GrandpaJustification {
+	round: u64,
+	commit: Commit<Hash, BlockNumber, AuthoritySignature, AuthorityId>,
+	votes_ancestries: Vec<Header>,
+}
+
+impl Certificate {
+	/// Check the certificate against a trusted authority set, returning the certified claim.
+	pub fn verify(&self, authority_set: &AuthoritySet) -> Result<&ClaimJson, String> {
+		verify_justification(&self.header, &self.justification, authority_set)?;
+
+		let digest = Digest::from(self.digest.clone());
+		let proof = self.proof.iter().map(|node| node.0.clone()).collect();
+		let claim = substrate_poe_proof::verify_claim_proof(&self.header, &digest, proof)
+			.map_err(|err| err.to_string())?
+			.ok_or("The storage proof shows that the digest is not claimed")?;
+		if ClaimJson::from(claim) != self.claim {
+			return Err("The claim does not match the one in the storage proof".into());
+		}
+
+		Ok(&self.claim)
+	}
+}
+
+// Check that `justification` proves the finality of `header`: more than two thirds of the weight
+// of the authority set signed precommits for the header or one of its descendants.
+fn verify_justification(header: &Header, justification: &[u8], authority_set: &AuthoritySet) -> Result<(), String> {
+	let justification = GrandpaJustification::decode(&mut &justification[..])
+		.map_err(|_| "Invalid GRANDPA justification".to_string())?;
+	let hash = header.hash();
+	if justification.commit.target_hash != hash {
+		return Err("The justification does not finalize the certified block".into());
+	}
+
+	let ancestries: HashMap<_, _> = justification.votes_ancestries.iter()
+		.map(|ancestor| (ancestor.hash(), ancestor))
+		.collect();
+	let descends_from_header = |mut block: Hash| loop {
+		if block == hash {
+			return true;
+		}
+		match ancestries.get(&block) {
+			Some(ancestor) => block = *ancestor.parent_hash(),
+			None => return false,
+		}
+	};
+
+	let weights: HashMap<_, _> = authority_set.authorities.iter().cloned().collect();
+	let mut signers = HashMap::new();
+	for signed in &justification.commit.precommits {
+		let weight = match weights.get(&signed.id) {
+			Some(weight) => *weight,
+			None => return Err("The justification is signed by an unknown authority".into()),
+		};
+		let message = Message::Precommit(signed.precommit.clone());
+		let payload = (message, justification.round, authority_set.set_id).encode();
+		if !AuthorityPair::verify(&signed.signature, &payload, &signed.id) {
+			return Err("The justification holds an invalid signature".into());
+		}
+		if descends_from_header(signed.precommit.target_hash) {
+			signers.insert(signed.id.clone(), weight);
+		}
+	}
+
+	let total: AuthorityWeight = weights.values().sum();
+	let signed: AuthorityWeight = signers.values().sum();
+	if total == 0 || signed < total - (total - 1) / 3 {
+		return Err("The justification is not signed by a supermajority of the authority set".into());
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use finality_grandpa::{Precommit, SignedPrecommit};
+	use primitives::Blake2Hasher;
+	use substrate_poe_runtime::{Poe, poe::HashAlgorithm};
+	use substrate_poe_proof::Claim;
+	use substrate_state_machine::{backend::{Backend, InMemory}, prove_read};
+
+	const ROUND: u64 = 3;
+	const SET_ID: u64 = 1;
+
+	type Signed = SignedPrecommit<Hash, BlockNumber, AuthoritySignature, AuthorityId>;
+
+	fn pair(n: u8) -> AuthorityPair {
+		AuthorityPair::from_seed(&[n; 32])
+	}
+
+	// Four authorities of equal weight, of which three form a supermajority.
+	fn authority_set() -> AuthoritySet {
+		AuthoritySet { set_id: SET_ID, authorities: (0..4).map(|n| (pair(n).public(), 1)).collect() }
+	}
+
+	fn header(number: BlockNumber, parent_hash: Hash, state_root: Hash) -> Header {
+		Header::new(number, Default::default(), state_root, parent_hash, Default::default())
+	}
+
+	fn signed(pair: &AuthorityPair, target: &Header, set_id: u64) -> Signed {
+		let precommit = Precommit { target_hash: target.hash(), target_number: *target.number() };
+		let payload = (Message::Precommit(precommit.clone()), ROUND, set_id).encode();
+		Signed { precommit, signature: pair.sign(&payload), id: pair.public() }
+	}
+
+	fn signed_by(signers: &[u8], target: &Header) -> Vec<Signed> {
+		signers.iter().map(|n| signed(&pair(*n), target, SET_ID)).collect()
+	}
+
+	fn justification(header: &Header, precommits: Vec<Signed>, votes_ancestries: Vec<Header>) -> Vec<u8> {
+		let commit = Commit { target_hash: header.hash(), target_number: *header.number(), precommits };
+		GrandpaJustification { round: ROUND, commit, votes_ancestries }.encode()
+	}
+
+	fn verify(header: &Header, precommits: Vec<Signed>, votes_ancestries: Vec<Header>) -> Result<(), String> {
+		verify_justification(header, &justification(header, precommits, votes_ancestries), &authority_set())
+	}
+
+	#[test]
+	fn supermajority_finalizes_the_header() {
+		let block = header(10, Hash::repeat_byte(1), Default::default());
+		assert_eq!(verify(&block, signed_by(&[0, 1, 2], &block), vec![]), Ok(()));
+
+		// Precommits for descendants count, given the headers linking them to the block
+		let child = header(11, block.hash(), Default::default());
+		let grandchild = header(12, child.hash(), Default::default());
+		let precommits = signed_by(&[0, 1], &grandchild).into_iter().chain(signed_by(&[2], &child)).collect();
+		assert_eq!(verify(&block, precommits, vec![grandchild, child]), Ok(()));
+	}
+
+	#[test]
+	fn precommits_below_the_threshold_are_rejected() {
+		let block = header(10, Hash::repeat_byte(1), Default::default());
+		let err = verify(&block, signed_by(&[0, 1], &block), vec![]).unwrap_err();
+		assert!(err.contains("supermajority"), "{}", err);
+
+		let empty = AuthoritySet { set_id: SET_ID, authorities: vec![] };
+		let justification = justification(&block, vec![], vec![]);
+		assert!(verify_justification(&block, &justification, &empty).is_err());
+	}
+
+	#[test]
+	fn precommits_of_another_set_are_rejected() {
+		let block = header(10, Hash::repeat_byte(1), Default::default());
+		let precommits = (0..3).map(|n| signed(&pair(n), &block, SET_ID + 1)).collect();
+		assert_eq!(verify(&block, precommits, vec![]), Err("The justification holds an invalid signature".into()));
+	}
+
+	#[test]
+	fn justification_of_another_block_is_rejected() {
+		let block = header(10, Hash::repeat_byte(1), Default::default());
+		let other = header(10, Hash::repeat_byte(2), Default::default());
+		let justification = justification(&other, signed_by(&[0, 1, 2], &other), vec![]);
+		assert_eq!(
+			verify_justification(&block, &justification, &authority_set()),
+			Err("The justification does not finalize the certified block".into()),
+		);
+	}
+
+	#[test]
+	fn unknown_signers_are_rejected() {
+		let block = header(10, Hash::repeat_byte(1), Default::default());
+		let precommits = signed_by(&[0, 1, 2, 4], &block);
+		assert_eq!(
+			verify(&block, precommits, vec![]),
+			Err("The justification is signed by an unknown authority".into()),
+		);
+	}
+
+	#[test]
+	fn authorities_signing_twice_are_counted_once() {
+		let block = header(10, Hash::repeat_byte(1), Default::default());
+		let child = header(11, block.hash(), Default::default());
+		let precommits = signed_by(&[0, 0, 1], &block).into_iter().chain(signed_by(&[1], &child)).collect();
+		let err = verify(&block, precommits, vec![child]).unwrap_err();
+		assert!(err.contains("supermajority"), "{}", err);
+	}
+
+	#[test]
+	fn precommits_for_other_branches_are_not_counted() {
+		let block = header(10, Hash::repeat_byte(1), Default::default());
+		let fork = header(10, Hash::repeat_byte(1), Hash::repeat_byte(9));
+		let precommits = signed_by(&[0, 1], &block).into_iter().chain(signed_by(&[2], &fork)).collect();
+		let err = verify(&block, precommits, vec![fork]).unwrap_err();
+		assert!(err.contains("supermajority"), "{}", err);
+
+		// Nor are descendants whose ancestry is missing
+		let child = header(11, block.hash(), Default::default());
+		let precommits = signed_by(&[0, 1], &block).into_iter().chain(signed_by(&[2], &child)).collect();
+		assert!(verify(&block, precommits, vec![]).is_err());
+	}
+
+	#[test]
+	fn certificates_round_trip() {
+		let digest = Digest { algorithm: HashAlgorithm::Sha256, bytes: vec![7; 32] };
+		let claim = Claim { owner: Default::default(), moment: 42, block_number: 7, extrinsic_index: 1, deposit: 1000 };
+		let key = Poe::proof_key(&digest);
+		let backend = InMemory::<Blake2Hasher>::from(vec![(None, key.to_vec(), Some(claim.encode()))]);
+		let state_root = backend.storage_root(std::iter::empty()).0;
+		let (_, proof) = prove_read(backend, &key).unwrap();
+
+		let block = header(10, Hash::repeat_byte(1), state_root);
+		let mut certificate = Certificate {
+			digest: digest.into(),
+			claim: claim.clone().into(),
+			justification: Bytes(justification(&block, signed_by(&[0, 1, 2], &block), vec![])),
+			header: block,
+			proof: proof.into_iter().map(Bytes).collect(),
+		};
+		assert!(certificate.verify(&authority_set()).unwrap() == &ClaimJson::from(claim));
+
+		// The certificate must be checked against the authorities that finalized the block
+		let mut other_set = authority_set();
+		other_set.set_id += 1;
+		assert!(certificate.verify(&other_set).is_err());
+
+		certificate.claim.moment += 1;
+		assert_eq!(
+			certificate.verify(&authority_set()).err(),
+			Some("The claim does not match the one in the storage proof".to_string()),
+		);
+	}
+}
@@ -0,0 +1,589 @@
+//! The subcommands the node adds to the standard substrate ones.
+
+use std::fs::File;
+use std::path::PathBuf;
+use codec::{Decode, Encode};
+use grandpa_primitives::{AuthorityId, AuthorityWeight, GRANDPA_AUTHORITIES_KEY};
+use primitives::{Bytes, H256, Pair, blake2_256, crypto::Ss58Codec, hexdisplay::HexDisplay, sr25519};
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use sr_primitives::{BuildStorage, generic::{BlockId, Era}, traits::ProvideRuntimeApi};
+use structopt::StructOpt;
+use substrate_cli::{error, create_config_with_db_path, GetLogFilter, SharedParams, VersionInfo};
+use substrate_poe_runtime::{
+	AccountId, Call, Hash, Index, MaxBatchSize, Poe, Runtime, SignedExtra, UncheckedExtrinsic,
+	benchmark::{self, BenchmarkParams},
+	merkle::MerkleTree,
+	poe::{self, Digest, HashAlgorithm},
+	poe_api::PoeApi,
+};
+use crate::{
+	certificate::{AuthoritySet, Certificate},
+	cli::load_spec,
+	hashing,
+	rpc::{ClaimJson, DigestJson, FileVerification},
+	rpc_client::RpcClient,
+};
+
+/// The custom subcommands of the node.
+#[derive(Clone, Debug, StructOpt)]
+pub enum CustomSubcommands {
+	/// Benchmark the dispatchables of the poe module and print their weight table.
+	#[structopt(name = "benchmark")]
+	Benchmark(BenchmarkCmd),
+
+	/// Hash a file and claim its digest on a running node.
+	#[structopt(name = "claim")]
+	Claim(ClaimCmd),
+
+	/// Hash files and anchor the Merkle root of their digests, writing the inclusion proof of each file.
+	#[structopt(name = "anchor")]
+	Anchor(AnchorCmd),
+
+	/// Hash a file and check whether its digest is claimed, exiting with a non-zero code if not.
+	#[structopt(name = "verify")]
+	Verify(VerifyCmd),
+
+	/// Export a certificate proving that a file is claimed, to be checked offline.
+	#[structopt(name = "export-certificate")]
+	ExportCertificate(ExportCertificateCmd),
+
+	/// Check a certificate exported by `export-certificate` against a trusted authority set.
+	#[structopt(name = "verify-certificate")]
+	VerifyCertificate(VerifyCertificateCmd),
+}
+
+impl GetLogFilter for CustomSubcommands {
+	fn get_log_filter(&self) -> Option<String> {
+		match self {
+			CustomSubcommands::Verify(cmd) => cmd.shared_params.log.clone(),
+			CustomSubcommands::ExportCertificate(cmd) => cmd.shared_params.log.clone(),
+			CustomSubcommands::VerifyCertificate(cmd) => cmd.shared_params.log.clone(),
+			_ => None,
+		}
+	}
+}
+
+impl CustomSubcommands {
+	/// Run the subcommand.
+	pub fn run(self, version: &VersionInfo) -> error::Result<()> {
+		match self {
+			CustomSubcommands::Benchmark(cmd) => cmd.run(),
+			CustomSubcommands::Claim(cmd) => cmd.run(),
+			CustomSubcommands::Anchor(cmd) => cmd.run(),
+			CustomSubcommands::Verify(cmd) => cmd.run(version),
+			CustomSubcommands::ExportCertificate(cmd) => cmd.run(version),
+			CustomSubcommands::VerifyCertificate(cmd) => cmd.run(version),
+		}
+	}
+}
+
+/// The `benchmark` subcommand.
+#[derive(Clone, Debug, StructOpt)]
+pub struct BenchmarkCmd {
+	/// The numbers of claims to populate storage with, benchmarking each of them.
+	#[structopt(long = "existing-claims", default_value = "0,1000,10000", raw(use_delimiter = "true"))]
+	pub existing_claims: Vec<u32>,
+
+	/// The algorithm of the claimed digests: sha256, blake2b256, keccak256 or sha3_512.
+	#[structopt(long = "algorithm", default_value = "sha256", parse(try_from_str = "parse_algorithm"))]
+	pub algorithm: HashAlgorithm,
+
+	/// The number of digests claimed at once when benchmarking `create_claims`, at most the maximum
+	/// batch size of the runtime.
+	#[structopt(long = "batch-size", default_value = "10")]
+	pub batch_size: u32,
+
+	/// The number of measured calls, over which the results are averaged.
+	#[structopt(long = "repeat", default_value = "20")]
+	pub repeat: u32,
+}
+
+impl BenchmarkCmd {
+	/// Run the benchmarks against the native runtime and print the weight table.
+	pub fn run(self) -> error::Result<()> {
+		let max_batch_size = MaxBatchSize::get();
+		if self.batch_size == 0 || self.batch_size > max_batch_size {
+			return Err(error::Error::Other(format!(
+				"--batch-size must be between 1 and {}, the maximum batch size of the runtime", max_batch_size,
+			)));
+		}
+
+		let mut results = Vec::new();
+		for &existing_claims in &self.existing_claims {
+			let params = BenchmarkParams {
+				existing_claims,
+				algorithm: self.algorithm,
+				batch_size: self.batch_size,
+				repeat: self.repeat,
+			};
+			let measured = benchmark::run::<Runtime>(&params)
+				.map_err(|err| error::Error::Other(format!("Benchmark failed: {}", err)))?;
+			results.extend(measured);
+		}
+
+		print!("{}", benchmark::format_table(&results));
+		Ok(())
+	}
+}
+
+/// The `claim` subcommand.
+#[derive(Clone, Debug, StructOpt)]
+pub struct ClaimCmd {
+	/// The file to claim.
+	#[structopt(parse(from_os_str))]
+	pub file: PathBuf,
+
+	/// The secret URI of the claiming account, such as `//Alice` or a mnemonic phrase.
+	#[structopt(long = "suri")]
+	pub suri: String,
+
+	/// The algorithm to hash the file with: sha256, blake2b256, keccak256 or sha3_512.
+	#[structopt(long = "algo", default_value = "sha256", parse(try_from_str = "parse_algorithm"))]
+	pub algorithm: HashAlgorithm,
+
+	/// The WebSocket RPC endpoint of the node to submit the claim to.
+	#[structopt(long = "url", default_value = "ws://127.0.0.1:9944")]
+	pub url: String,
+}
+
+impl ClaimCmd {
+	/// Claim the digest of the file and wait until the claim is included in a block.
+	pub fn run(self) -> error::Result<()> {
+		let digest = hashing::hash_file(&self.file, self.algorithm)
+			.map_err(|err| error::Error::Other(format!("Unable to read {}: {}", self.file.display(), err)))?;
+		let pair = sr25519::Pair::from_string(&self.suri, None)
+			.map_err(|err| error::Error::Other(format!("Invalid secret URI: {:?}", err)))?;
+		let owner: AccountId = pair.public();
+		println!("Digest: 0x{}", HexDisplay::from(&digest.bytes));
+
+		let mut client = RpcClient::connect(&self.url).map_err(error::Error::Other)?;
+		let call = Call::Poe(poe::Call::create_claim(digest.clone(), None, None));
+		let block = submit_call(&mut client, &pair, call).map_err(error::Error::Other)?;
+
+		// The extrinsic is included even if the claim failed, so check that it was recorded
+		let claim = client.request("poe_getClaim", json!([DigestJson::from(digest), block]))
+			.map_err(error::Error::Other)?;
+		if claim["owner"] != owner.to_ss58check() {
+			return Err(error::Error::Other(format!(
+				"The claim was not recorded in block {}: the digest may already be claimed, or the account may \
+				not be able to pay the deposit",
+				block,
+			)));
+		}
+
+		println!("Claimed by {} in block {}", owner.to_ss58check(), block);
+		Ok(())
+	}
+}
+
+/// The `anchor` subcommand.
+#[derive(Clone, Debug, StructOpt)]
+pub struct AnchorCmd {
+	/// The files to anchor, in the order of the leaves of the tree.
+	#[structopt(parse(from_os_str), raw(required = "true"))]
+	pub files: Vec<PathBuf>,
+
+	/// The algorithm to hash the files with: sha256, blake2b256, keccak256 or sha3_512.
+	#[structopt(long = "algo", default_value = "sha256", parse(try_from_str = "parse_algorithm"))]
+	pub algorithm: HashAlgorithm,
+
+	/// Where to write the root of the tree and the inclusion proof of each file.
+	#[structopt(long = "output", short = "o", parse(from_os_str))]
+	pub output: PathBuf,
+
+	/// The secret URI of the account anchoring the root, such as `//Alice` or a mnemonic phrase. The
+	/// root is only written to `--output` if not given.
+	#[structopt(long = "suri")]
+	pub suri: Option<String>,
+
+	/// The WebSocket RPC endpoint of the node to submit the anchor to.
+	#[structopt(long = "url", default_value = "ws://127.0.0.1:9944")]
+	pub url: String,
+}
+
+/// The Merkle root of anchored files, with the inclusion proof of each of them.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorJson {
+	/// The root of the tree of the digests of the files.
+	pub root: H256,
+	/// The number of leaves of the tree.
+	pub leaf_count: u64,
+	/// The files, in the order of the leaves.
+	pub documents: Vec<AnchoredDocument>,
+}
+
+/// A file anchored with a Merkle root.
+#[derive(Serialize, Deserialize)]
+pub struct AnchoredDocument {
+	/// The path of the file.
+	pub file: PathBuf,
+	/// Its digest, the leaf of the tree.
+	pub digest: DigestJson,
+	/// The sibling hashes from the leaf up to the root, as expected by `poe_verifyInclusion`.
+	pub proof: Vec<H256>,
+}
+
+impl AnchorCmd {
+	/// Write the root and the proofs of the tree of the files, then anchor the root if `--suri` is
+	/// given and wait until it is included in a block.
+	pub fn run(self) -> error::Result<()> {
+		let mut digests = Vec::with_capacity(self.files.len());
+		for file in &self.files {
+			digests.push(hashing::hash_file(file, self.algorithm)
+				.map_err(|err| error::Error::Other(format!("Unable to read {}: {}", file.display(), err)))?);
+		}
+		let tree = MerkleTree::new(&digests).ok_or_else(|| error::Error::Other("No file to anchor".into()))?;
+		let documents = self.files.iter().zip(digests).enumerate()
+			.map(|(index, (file, digest))| AnchoredDocument {
+				file: file.clone(),
+				digest: digest.into(),
+				proof: tree.proof(index).expect("the tree has a leaf for each file; qed"),
+			})
+			.collect();
+		let anchor = AnchorJson { root: tree.root(), leaf_count: tree.leaf_count(), documents };
+
+		let output = File::create(&self.output)?;
+		serde_json::to_writer_pretty(output, &anchor)
+			.map_err(|err| error::Error::Other(format!("Unable to write the anchor: {}", err)))?;
+		println!("Root: 0x{}", HexDisplay::from(anchor.root.as_bytes()));
+		println!("Proofs of {} files written to {}", anchor.leaf_count, self.output.display());
+
+		let suri = match &self.suri {
+			Some(suri) => suri,
+			None => return Ok(()),
+		};
+		let pair = sr25519::Pair::from_string(suri, None)
+			.map_err(|err| error::Error::Other(format!("Invalid secret URI: {:?}", err)))?;
+		let owner: AccountId = pair.public();
+
+		let mut client = RpcClient::connect(&self.url).map_err(error::Error::Other)?;
+		let call = Call::Poe(poe::Call::create_anchor(anchor.root, anchor.leaf_count));
+		let block = submit_call(&mut client, &pair, call).map_err(error::Error::Other)?;
+
+		// The extrinsic is included even if anchoring failed, so check that the files can be proven
+		let first = &anchor.documents[0];
+		let included = client.request("poe_verifyInclusion", json!([anchor.root, first.digest, first.proof, block]))
+			.map_err(error::Error::Other)?;
+		if included != true {
+			return Err(error::Error::Other(format!(
+				"The anchor was not recorded in block {}: the account may not be able to pay the deposit",
+				block,
+			)));
+		}
+
+		println!("Anchored by {} in block {}", owner.to_ss58check(), block);
+		Ok(())
+	}
+}
+
+/// The `verify` subcommand.
+#[derive(Clone, Debug, StructOpt)]
+pub struct VerifyCmd {
+	/// The file to verify.
+	#[structopt(parse(from_os_str))]
+	pub file: PathBuf,
+
+	/// The algorithm the file was hashed with when claimed: sha256, blake2b256, keccak256 or sha3_512.
+	#[structopt(long = "algo", default_value = "sha256", parse(try_from_str = "parse_algorithm"))]
+	pub algorithm: HashAlgorithm,
+
+	/// The WebSocket RPC endpoint of the node to query, unless `--base-path` is given.
+	#[structopt(long = "url", default_value = "ws://127.0.0.1:9944")]
+	pub url: String,
+
+	/// The chain and database to read the claim from instead, if `--base-path` is given. The node
+	/// using the database must be stopped.
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl VerifyCmd {
+	/// Print the claim of the digest of the file, or exit with code 1 if it is not claimed.
+	pub fn run(self, version: &VersionInfo) -> error::Result<()> {
+		let digest = hashing::hash_file(&self.file, self.algorithm)
+			.map_err(|err| error::Error::Other(format!("Unable to read {}: {}", self.file.display(), err)))?;
+		println!("Digest: 0x{}", HexDisplay::from(&digest.bytes));
+
+		let claim = match self.shared_params.base_path {
+			Some(_) => self.local_claim(digest, version)?,
+			None => self.remote_claim(digest).map_err(error::Error::Other)?,
+		};
+
+		match claim {
+			Some(claim) => {
+				print_claim(&claim);
+				Ok(())
+			},
+			None => {
+				println!("Not claimed");
+				std::process::exit(1)
+			},
+		}
+	}
+
+	// The claim of `digest` on the node at `url`.
+	fn remote_claim(&self, digest: Digest) -> Result<Option<ClaimJson>, String> {
+		let mut client = RpcClient::connect(&self.url)?;
+		let params = json!([Bytes(digest.bytes), digest.algorithm]);
+		let verification = from_value::<FileVerification>(client.request("poe_verifyFile", params)?)?;
+		Ok(verification.claim)
+	}
+
+	// The claim of `digest` at the best block of the local database.
+	fn local_claim(&self, digest: Digest, version: &VersionInfo) -> error::Result<Option<ClaimJson>> {
+		let config = create_config_with_db_path::<(), _, _, _>(load_spec, &self.shared_params, version)?;
+		let client = new_full_start!(config).0.client();
+		let at = BlockId::hash(client.info().chain.best_hash);
+		let claim = client.runtime_api().claim(&at, digest)
+			.map_err(|err| error::Error::Other(format!("Unable to query the runtime: {:?}", err)))?;
+		Ok(claim.map(ClaimJson::from))
+	}
+}
+
+/// The `export-certificate` subcommand.
+#[derive(Clone, Debug, StructOpt)]
+pub struct ExportCertificateCmd {
+	/// The claimed file.
+	#[structopt(parse(from_os_str))]
+	pub file: PathBuf,
+
+	/// The algorithm the file was hashed with when claimed: sha256, blake2b256, keccak256 or sha3_512.
+	#[structopt(long = "algo", default_value = "sha256", parse(try_from_str = "parse_algorithm"))]
+	pub algorithm: HashAlgorithm,
+
+	/// Where to write the certificate.
+	#[structopt(long = "output", short = "o", parse(from_os_str))]
+	pub output: PathBuf,
+
+	/// The chain and database to read the claim from. The node using the database must be stopped.
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl ExportCertificateCmd {
+	/// Write the certificate of the claim of the file, as of the first block finalized with a
+	/// stored justification since the claim.
+	pub fn run(self, version: &VersionInfo) -> error::Result<()> {
+		let digest = hashing::hash_file(&self.file, self.algorithm)
+			.map_err(|err| error::Error::Other(format!("Unable to read {}: {}", self.file.display(), err)))?;
+		let config = create_config_with_db_path::<(), _, _, _>(load_spec, &self.shared_params, version)?;
+		let client = new_full_start!(config).0.client();
+		let client_error = |err| error::Error::Other(format!("Unable to read the database: {:?}", err));
+
+		let finalized = client.info().chain.finalized_number;
+		let claim = client.runtime_api().claim(&BlockId::number(finalized), digest.clone())
+			.map_err(client_error)?
+			.ok_or_else(|| error::Error::Other("The file is not claimed as of the last finalized block".into()))?;
+
+		// GRANDPA only stores the justifications of some blocks, look for the first one since the claim
+		let mut justified = None;
+		for number in claim.block_number..=finalized {
+			if let Some(justification) = client.justification(&BlockId::number(number)).map_err(client_error)? {
+				justified = Some((BlockId::number(number), justification));
+				break;
+			}
+		}
+		let (at, justification) = justified.ok_or_else(|| error::Error::Other(
+			"No block finalized since the claim has a stored justification yet, retry later".into()
+		))?;
+
+		let claim = client.runtime_api().claim(&at, digest.clone()).map_err(client_error)?
+			.ok_or_else(|| error::Error::Other("The claim was revoked after it was finalized".into()))?;
+		let header = client.header(&at).map_err(client_error)?
+			.ok_or_else(|| error::Error::Other("Missing header of a finalized block".into()))?;
+		let proof = client.read_proof(&at, &Poe::proof_key(&digest)).map_err(client_error)?;
+
+		let certificate = Certificate {
+			digest: digest.into(),
+			claim: claim.into(),
+			header,
+			proof: proof.into_iter().map(Bytes).collect(),
+			justification: Bytes(justification),
+		};
+		let output = File::create(&self.output)?;
+		serde_json::to_writer_pretty(output, &certificate)
+			.map_err(|err| error::Error::Other(format!("Unable to write the certificate: {}", err)))?;
+
+		println!("Certificate of block #{} written to {}", certificate.header.number, self.output.display());
+		Ok(())
+	}
+}
+
+/// The `verify-certificate` subcommand.
+#[derive(Clone, Debug, StructOpt)]
+pub struct VerifyCertificateCmd {
+	/// The certificate to check.
+	#[structopt(parse(from_os_str))]
+	pub certificate: PathBuf,
+
+	/// A JSON file with the trusted authority set (`setId` and `authorities`), if it changed since
+	/// genesis. The genesis authorities of `--chain` are trusted otherwise.
+	#[structopt(long = "authority-set", parse(from_os_str))]
+	pub authority_set: Option<PathBuf>,
+
+	/// The chain whose genesis authorities are trusted.
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl VerifyCertificateCmd {
+	/// Check the certificate without network access, failing if it is not valid.
+	pub fn run(self, version: &VersionInfo) -> error::Result<()> {
+		let certificate: Certificate = read_json(&self.certificate)?;
+		let authority_set = match &self.authority_set {
+			Some(path) => read_json(path)?,
+			None => self.genesis_authority_set(version)?,
+		};
+
+		let claim = certificate.verify(&authority_set).map_err(error::Error::Other)?;
+		println!("Valid certificate for digest 0x{}", HexDisplay::from(&certificate.digest.bytes.0));
+		print_claim(claim);
+		println!("Finalized in block #{} by authority set {}", certificate.header.number, authority_set.set_id);
+		Ok(())
+	}
+
+	// The GRANDPA authorities in the genesis storage of the chain.
+	fn genesis_authority_set(&self, version: &VersionInfo) -> error::Result<AuthoritySet> {
+		let config = create_config_with_db_path::<(), _, _, _>(load_spec, &self.shared_params, version)?;
+		let (storage, _) = config.chain_spec.build_storage().map_err(error::Error::Other)?;
+		let authorities = storage.get(GRANDPA_AUTHORITIES_KEY)
+			.and_then(|authorities| Vec::<(AuthorityId, AuthorityWeight)>::decode(&mut &authorities[..]).ok())
+			.ok_or_else(|| error::Error::Other("The chain has no genesis GRANDPA authorities".into()))?;
+		Ok(AuthoritySet { set_id: 0, authorities })
+	}
+}
+
+fn print_claim(claim: &ClaimJson) {
+	println!("Claimed by {}", claim.owner);
+	println!("Timestamp: {} ms since the unix epoch", claim.moment);
+	println!("Block: #{}, extrinsic {}", claim.block_number, claim.extrinsic_index);
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &PathBuf) -> error::Result<T> {
+	serde_json::from_reader(File::open(path)?)
+		.map_err(|err| error::Error::Other(format!("Invalid {}: {}", path.display(), err)))
+}
+
+// Submit `call` signed by `pair` and wait until it is included in a block, returning the hash of
+// that block.
+fn submit_call(client: &mut RpcClient, pair: &sr25519::Pair, call: Call) -> Result<Value, String> {
+	let genesis_hash = from_value::<Hash>(client.request("chain_getBlockHash", json!([0]))?)?;
+	let version = client.request("state_getRuntimeVersion", json!([]))?;
+	let spec_version = version["specVersion"].as_u64().ok_or("Invalid runtime version")? as u32;
+	let nonce = account_nonce(client, &pair.public())?;
+
+	let extrinsic = signed_extrinsic(pair, call, nonce, spec_version, genesis_hash);
+	let subscription = client.request("author_submitAndWatchExtrinsic", json!([Bytes(extrinsic.encode())]))?;
+
+	loop {
+		let status = client.notification(&subscription)?;
+		if let Some(block) = status.get("finalized") {
+			return Ok(block.clone());
+		}
+		if status != "ready" && status != "future" && status.get("broadcast").is_none() {
+			return Err(format!("The extrinsic was not included: {}", status));
+		}
+	}
+}
+
+// The nonce of `account`, read from the `System AccountNonce` map.
+fn account_nonce(client: &mut RpcClient, account: &AccountId) -> Result<Index, String> {
+	let mut key = b"System AccountNonce".to_vec();
+	account.encode_to(&mut key);
+	let value = client.request("state_getStorage", json!([Bytes(blake2_256(&key).to_vec())]))?;
+	if value.is_null() {
+		return Ok(0);
+	}
+	let value = from_value::<Bytes>(value)?;
+	Index::decode(&mut &value.0[..]).map_err(|_| "Invalid account nonce".to_string())
+}
+
+// An extrinsic dispatching `call`, signed by `pair` as its `nonce`th transaction.
+fn signed_extrinsic(
+	pair: &sr25519::Pair,
+	call: Call,
+	nonce: Index,
+	spec_version: u32,
+	genesis_hash: Hash,
+) -> UncheckedExtrinsic {
+	let extra: SignedExtra = (
+		system::CheckVersion::new(),
+		system::CheckGenesis::new(),
+		system::CheckEra::from(Era::Immortal),
+		system::CheckNonce::from(nonce),
+		system::CheckWeight::new(),
+		balances::TakeFees::from(0),
+		poe::CheckPoeClaim::new(),
+	);
+	// What the signed extensions add to the signed payload, without including it in the extrinsic
+	let additional_signed = (spec_version, genesis_hash, genesis_hash, (), (), (), ());
+	let payload = (call, extra, additional_signed);
+	let signature = payload.using_encoded(|payload| if payload.len() > 256 {
+		pair.sign(&blake2_256(payload))
+	} else {
+		pair.sign(payload)
+	});
+
+	let (call, extra, _) = payload;
+	UncheckedExtrinsic::new_signed(call, pair.public().into(), signature.into(), extra)
+}
+
+fn from_value<T: serde::de::DeserializeOwned>(value: Value) -> Result<T, String> {
+	serde_json::from_value(value).map_err(|err| format!("Invalid response from the node: {}", err))
+}
+
+/// Parse the name of a hash algorithm, as it is serialized.
+pub fn parse_algorithm(name: &str) -> Result<HashAlgorithm, String> {
+	match name {
+		"sha256" => Ok(HashAlgorithm::Sha256),
+		"blake2b256" => Ok(HashAlgorithm::Blake2b256),
+		"keccak256" => Ok(HashAlgorithm::Keccak256),
+		"sha3_512" => Ok(HashAlgorithm::Sha3_512),
+		_ => Err(format!("Unknown hash algorithm: {}", name)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::Blake2Hasher;
+	use sr_io::{with_externalities, TestExternalities};
+	use sr_primitives::traits::Checkable;
+	use substrate_poe_runtime::VERSION;
+
+	fn digest(n: u8) -> Digest {
+		Digest { algorithm: HashAlgorithm::Sha256, bytes: vec![n; 32] }
+	}
+
+	#[test]
+	fn signed_extrinsics_pass_the_runtime_checks() {
+		let storage = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+		let mut ext: TestExternalities<Blake2Hasher> = storage.into();
+
+		with_externalities(&mut ext, || {
+			let pair = sr25519::Pair::from_seed(&[1; 32]);
+			let genesis_hash = system::Module::<Runtime>::block_hash(0);
+			let context = system::ChainContext::<Runtime>::default();
+
+			// Short payloads are signed whole, long ones through their hash
+			let claim = Call::Poe(poe::Call::create_claim(digest(0), None, None));
+			let batch = Call::Poe(poe::Call::create_claims((0..10).map(digest).collect()));
+			assert!(batch.encode().len() > 256);
+			for call in vec![claim, batch] {
+				let extrinsic = signed_extrinsic(&pair, call.clone(), 7, VERSION.spec_version, genesis_hash);
+				let checked = extrinsic.check(&context).unwrap();
+				assert_eq!(checked.signed.map(|(who, _)| who), Some(AccountId::from(pair.public())));
+				assert_eq!(checked.function, call);
+			}
+
+			// The signature covers the chain and runtime the extrinsic is meant for
+			let call = Call::Poe(poe::Call::create_claim(digest(0), None, None));
+			let extrinsic = signed_extrinsic(&pair, call.clone(), 7, VERSION.spec_version + 1, genesis_hash);
+			assert!(extrinsic.check(&context).is_err());
+			let extrinsic = signed_extrinsic(&pair, call, 7, VERSION.spec_version, H256::repeat_byte(1));
+			assert!(extrinsic.check(&context).is_err());
+		});
+	}
+}
@@ -0,0 +1,124 @@
+//! Hashing of files into proof digests, with any of the algorithms the poe module accepts.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use sha2::Digest as _;
+use substrate_poe_runtime::poe::{Digest, HashAlgorithm};
+
+// Files are streamed through the hasher in chunks of this size.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+enum Hasher {
+	Sha256(sha2::Sha256),
+	Blake2b256(blake2_rfc::blake2b::Blake2b),
+	Keccak256(tiny_keccak::Keccak),
+	Sha3_512(sha3::Sha3_512),
+}
+
+impl Hasher {
+	fn new(algorithm: HashAlgorithm) -> Self {
+		match algorithm {
+			HashAlgorithm::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+			HashAlgorithm::Blake2b256 => Hasher::Blake2b256(blake2_rfc::blake2b::Blake2b::new(32)),
+			HashAlgorithm::Keccak256 => Hasher::Keccak256(tiny_keccak::Keccak::new_keccak256()),
+			HashAlgorithm::Sha3_512 => Hasher::Sha3_512(sha3::Sha3_512::new()),
+		}
+	}
+
+	fn update(&mut self, data: &[u8]) {
+		match self {
+			Hasher::Sha256(hasher) => hasher.input(data),
+			Hasher::Blake2b256(hasher) => hasher.update(data),
+			Hasher::Keccak256(hasher) => hasher.update(data),
+			Hasher::Sha3_512(hasher) => hasher.input(data),
+		}
+	}
+
+	fn finalize(self) -> Vec<u8> {
+		match self {
+			Hasher::Sha256(hasher) => hasher.result().to_vec(),
+			Hasher::Blake2b256(hasher) => hasher.finalize().as_bytes().to_vec(),
+			Hasher::Keccak256(hasher) => {
+				let mut hash = vec![0; 32];
+				hasher.finalize(&mut hash);
+				hash
+			},
+			Hasher::Sha3_512(hasher) => hasher.result().to_vec(),
+		}
+	}
+}
+
+/// Hash everything read from `reader` with `algorithm`.
+pub fn hash_reader(mut reader: impl Read, algorithm: HashAlgorithm) -> io::Result<Digest> {
+	let mut hasher = Hasher::new(algorithm);
+	let mut chunk = vec![0; CHUNK_SIZE];
+	loop {
+		match reader.read(&mut chunk) {
+			Ok(0) => break,
+			Ok(read) => hasher.update(&chunk[..read]),
+			Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+			Err(err) => return Err(err),
+		}
+	}
+	Ok(Digest { algorithm, bytes: hasher.finalize() })
+}
+
+/// Hash the file at `path` with `algorithm`, without loading it whole in memory.
+pub fn hash_file(path: &Path, algorithm: HashAlgorithm) -> io::Result<Digest> {
+	hash_reader(File::open(path)?, algorithm)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::hexdisplay::HexDisplay;
+
+	fn hex(algorithm: HashAlgorithm, data: &[u8]) -> String {
+		let digest = hash_reader(data, algorithm).unwrap();
+		assert_eq!(digest.algorithm, algorithm);
+		assert_eq!(digest.bytes.len(), algorithm.digest_len());
+		HexDisplay::from(&digest.bytes).to_string()
+	}
+
+	#[test]
+	fn sha256_matches_known_answers() {
+		let algorithm = HashAlgorithm::Sha256;
+		assert_eq!(hex(algorithm, b""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+		assert_eq!(hex(algorithm, b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+		// Spans several chunks
+		assert_eq!(
+			hex(algorithm, &vec![b'a'; 1_000_000]),
+			"cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+		);
+	}
+
+	#[test]
+	fn blake2b256_matches_known_answers() {
+		let algorithm = HashAlgorithm::Blake2b256;
+		assert_eq!(hex(algorithm, b""), "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8");
+		assert_eq!(hex(algorithm, b"abc"), "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319");
+	}
+
+	#[test]
+	fn keccak256_matches_known_answers() {
+		let algorithm = HashAlgorithm::Keccak256;
+		assert_eq!(hex(algorithm, b""), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+		assert_eq!(hex(algorithm, b"abc"), "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+	}
+
+	#[test]
+	fn sha3_512_matches_known_answers() {
+		let algorithm = HashAlgorithm::Sha3_512;
+		assert_eq!(
+			hex(algorithm, b""),
+			"a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a6\
+			15b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26"
+		);
+		assert_eq!(
+			hex(algorithm, b"abc"),
+			"b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+			10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+		);
+	}
+}
@@ -7,6 +7,11 @@ mod chain_spec;
 #[macro_use]
 mod service;
 mod cli;
+mod command;
+mod rpc;
+mod rpc_client;
+mod hashing;
+mod certificate;
 
 pub use substrate_cli::{VersionInfo, IntoExit, error};
 
@@ -0,0 +1,293 @@
+//! The `poe_*` JSON-RPC methods, letting frontends query claims through the `PoeApi` runtime API
+//! instead of decoding raw storage.
+
+use std::sync::Arc;
+use jsonrpc_core::{Error, ErrorCode, IoHandler, Metadata, Result};
+use jsonrpc_derive::rpc;
+use serde::{Serialize, Deserialize};
+use primitives::{Blake2Hasher, Bytes, H256, crypto::Ss58Codec};
+use sr_primitives::{generic::BlockId, traits::{Block as BlockT, Header as HeaderT, ProvideRuntimeApi}};
+use substrate_client::{backend::Backend, blockchain::HeaderBackend, CallExecutor, Client};
+use substrate_poe_runtime::{
+	AccountId, Balance, BlockNumber, Moment, Poe as PoeModule, opaque::Block,
+	poe::{ClaimInfo, Digest, HashAlgorithm, PoeStats},
+	poe_api::{AnchorApi, PoeApi as PoeRuntimeApi},
+};
+
+/// A digest, with its bytes hex encoded.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DigestJson {
+	/// The algorithm the digest was computed with.
+	pub algorithm: HashAlgorithm,
+	/// The digest itself.
+	pub bytes: Bytes,
+}
+
+impl From<DigestJson> for Digest {
+	fn from(digest: DigestJson) -> Self {
+		Digest { algorithm: digest.algorithm, bytes: digest.bytes.0 }
+	}
+}
+
+impl From<Digest> for DigestJson {
+	fn from(digest: Digest) -> Self {
+		DigestJson { algorithm: digest.algorithm, bytes: Bytes(digest.bytes) }
+	}
+}
+
+/// The record of a claimed digest.
+#[derive(PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimJson {
+	/// The SS58 address of the owner.
+	pub owner: String,
+	/// The timestamp of the block the claim was created in.
+	pub moment: Moment,
+	/// The block the claim was created in.
+	pub block_number: BlockNumber,
+	/// The index of the extrinsic that created the claim in its block.
+	pub extrinsic_index: u32,
+	/// The reserved deposit, as a decimal string since it may not fit in a JSON number.
+	pub deposit: String,
+}
+
+impl From<ClaimInfo<AccountId, Moment, BlockNumber, Balance>> for ClaimJson {
+	fn from(claim: ClaimInfo<AccountId, Moment, BlockNumber, Balance>) -> Self {
+		ClaimJson {
+			owner: claim.owner.to_ss58check(),
+			moment: claim.moment,
+			block_number: claim.block_number,
+			extrinsic_index: claim.extrinsic_index,
+			deposit: claim.deposit.to_string(),
+		}
+	}
+}
+
+/// The outcome of verifying a file against the claims.
+#[derive(Serialize, Deserialize)]
+pub struct FileVerification {
+	/// Whether the digest of the file has been claimed.
+	pub claimed: bool,
+	/// The claim of the digest, if any.
+	pub claim: Option<ClaimJson>,
+}
+
+/// A storage proof of the claim of a digest, to be checked with `substrate_poe_proof`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimProof<BlockHash> {
+	/// The block the proof is for.
+	pub at: BlockHash,
+	/// The state root of the block, which the proof is checked against.
+	pub state_root: H256,
+	/// The trie nodes on the path to the `Proofs` entry of the digest.
+	pub proof: Vec<Bytes>,
+}
+
+/// Clients able to prove the storage of a block.
+pub trait ReadProofProvider {
+	/// The trie nodes proving the value of `key` in the state of block `at`.
+	fn read_proof(&self, at: &BlockId<Block>, key: &[u8]) -> substrate_client::error::Result<Vec<Vec<u8>>>;
+}
+
+impl<B, E, RA> ReadProofProvider for Client<B, E, Block, RA> where
+	B: Backend<Block, Blake2Hasher>,
+	E: CallExecutor<Block, Blake2Hasher>,
+{
+	fn read_proof(&self, at: &BlockId<Block>, key: &[u8]) -> substrate_client::error::Result<Vec<Vec<u8>>> {
+		Client::read_proof(self, at, key)
+	}
+}
+
+/// The `poe_*` RPC methods.
+#[rpc]
+pub trait PoeApi<BlockHash> {
+	/// The claim of a digest, if any.
+	#[rpc(name = "poe_getClaim")]
+	fn claim(&self, digest: DigestJson, at: Option<BlockHash>) -> Result<Option<ClaimJson>>;
+
+	/// The digests claimed by the account of an SS58 address, by pages of at most
+	/// `MAX_CLAIMS_PER_PAGE`, starting from position `start` (0 by default).
+	#[rpc(name = "poe_getClaimsByOwner")]
+	fn claims_by_owner(
+		&self,
+		owner: String,
+		start: Option<u32>,
+		count: Option<u32>,
+		at: Option<BlockHash>,
+	) -> Result<Vec<DigestJson>>;
+
+	/// Whether the hex encoded digest of a file has been claimed. The digest is assumed to be
+	/// SHA-256 unless another algorithm is given.
+	#[rpc(name = "poe_verifyFile")]
+	fn verify_file(
+		&self,
+		digest: Bytes,
+		algorithm: Option<HashAlgorithm>,
+		at: Option<BlockHash>,
+	) -> Result<FileVerification>;
+
+	/// The number of claims and anchors recorded.
+	#[rpc(name = "poe_stats")]
+	fn stats(&self, at: Option<BlockHash>) -> Result<PoeStats>;
+
+	/// Whether a digest is one of the documents anchored with a Merkle root, given the sibling
+	/// hashes of its inclusion proof.
+	#[rpc(name = "poe_verifyInclusion")]
+	fn verify_inclusion(&self, root: H256, leaf: DigestJson, proof: Vec<H256>, at: Option<BlockHash>) -> Result<bool>;
+}
+
+/// The `poe_*` RPC methods proving the storage of blocks, only served by full nodes.
+#[rpc]
+pub trait PoeProofApi<BlockHash> {
+	/// A storage proof of the claim of a digest, or of its absence, against the state root of a
+	/// block.
+	#[rpc(name = "poe_getClaimProof")]
+	fn claim_proof(&self, digest: DigestJson, at: Option<BlockHash>) -> Result<ClaimProof<BlockHash>>;
+}
+
+/// The `poe_*` RPC methods, answered by the runtime of a client.
+pub struct Poe<C> {
+	client: Arc<C>,
+}
+
+impl<C> Poe<C> {
+	/// Create the RPC methods of a client.
+	pub fn new(client: Arc<C>) -> Self {
+		Poe { client }
+	}
+}
+
+/// The maximum number of digests returned at once by `poe_getClaimsByOwner`.
+pub const MAX_CLAIMS_PER_PAGE: u32 = 1_000;
+
+// Errors returned to RPC callers.
+const RUNTIME_ERROR: i64 = 1;
+const INVALID_ADDRESS: i64 = 2;
+const STATE_ERROR: i64 = 3;
+
+fn runtime_error(err: impl std::fmt::Debug) -> Error {
+	Error {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Unable to query the runtime".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+fn state_error(err: impl std::fmt::Debug) -> Error {
+	Error {
+		code: ErrorCode::ServerError(STATE_ERROR),
+		message: "Unable to read the state".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+impl<C> Poe<C> where
+	C: ProvideRuntimeApi + HeaderBackend<Block>,
+	C::Api: PoeRuntimeApi<Block, AccountId, Moment, BlockNumber, Balance>,
+{
+	// The block to query, defaulting to the best one.
+	fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+		BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+	}
+
+	fn get_claim(&self, digest: Digest, at: Option<<Block as BlockT>::Hash>) -> Result<Option<ClaimJson>> {
+		let claim = self.client.runtime_api().claim(&self.block_id(at), digest).map_err(runtime_error)?;
+		Ok(claim.map(ClaimJson::from))
+	}
+}
+
+impl<C> PoeApi<<Block as BlockT>::Hash> for Poe<C> where
+	C: ProvideRuntimeApi + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: PoeRuntimeApi<Block, AccountId, Moment, BlockNumber, Balance> + AnchorApi<Block>,
+{
+	fn claim(&self, digest: DigestJson, at: Option<<Block as BlockT>::Hash>) -> Result<Option<ClaimJson>> {
+		self.get_claim(digest.into(), at)
+	}
+
+	fn claims_by_owner(
+		&self,
+		owner: String,
+		start: Option<u32>,
+		count: Option<u32>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<DigestJson>> {
+		let owner = AccountId::from_ss58check(&owner).map_err(|err| Error {
+			code: ErrorCode::ServerError(INVALID_ADDRESS),
+			message: "Invalid SS58 address".into(),
+			data: Some(format!("{:?}", err).into()),
+		})?;
+		let start = start.unwrap_or(0);
+		let count = count.map_or(MAX_CLAIMS_PER_PAGE, |count| count.min(MAX_CLAIMS_PER_PAGE));
+		let digests = self.client.runtime_api()
+			.claims_of(&self.block_id(at), owner, start, count)
+			.map_err(runtime_error)?;
+		Ok(digests.into_iter().map(DigestJson::from).collect())
+	}
+
+	fn verify_file(
+		&self,
+		digest: Bytes,
+		algorithm: Option<HashAlgorithm>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<FileVerification> {
+		let digest = Digest { algorithm: algorithm.unwrap_or(HashAlgorithm::Sha256), bytes: digest.0 };
+		let claim = self.get_claim(digest, at)?;
+		Ok(FileVerification { claimed: claim.is_some(), claim })
+	}
+
+	fn stats(&self, at: Option<<Block as BlockT>::Hash>) -> Result<PoeStats> {
+		self.client.runtime_api().stats(&self.block_id(at)).map_err(runtime_error)
+	}
+
+	fn verify_inclusion(
+		&self,
+		root: H256,
+		leaf: DigestJson,
+		proof: Vec<H256>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<bool> {
+		self.client.runtime_api()
+			.verify_inclusion(&self.block_id(at), root, leaf.into(), proof)
+			.map_err(runtime_error)
+	}
+}
+
+impl<C> PoeProofApi<<Block as BlockT>::Hash> for Poe<C> where
+	C: HeaderBackend<Block> + ReadProofProvider + Send + Sync + 'static,
+{
+	fn claim_proof(
+		&self,
+		digest: DigestJson,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<ClaimProof<<Block as BlockT>::Hash>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let header = self.client.header(BlockId::hash(at)).map_err(state_error)?
+			.ok_or_else(|| state_error("Unknown block"))?;
+		let proof = self.client.read_proof(&BlockId::hash(at), &PoeModule::proof_key(&digest.into()))
+			.map_err(state_error)?;
+		Ok(ClaimProof { at, state_root: *header.state_root(), proof: proof.into_iter().map(Bytes).collect() })
+	}
+}
+
+/// The RPC extensions of a light client, which can't prove the storage of blocks.
+pub fn create_light<C, M>(client: Arc<C>) -> IoHandler<M> where
+	C: ProvideRuntimeApi + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: PoeRuntimeApi<Block, AccountId, Moment, BlockNumber, Balance> + AnchorApi<Block>,
+	M: Metadata + Default,
+{
+	let mut io = IoHandler::default();
+	io.extend_with(PoeApi::to_delegate(Poe::new(client)));
+	io
+}
+
+/// The RPC extensions of a full node, to be registered with the service.
+pub fn create_full<C, M>(client: Arc<C>) -> IoHandler<M> where
+	C: ProvideRuntimeApi + HeaderBackend<Block> + ReadProofProvider + Send + Sync + 'static,
+	C::Api: PoeRuntimeApi<Block, AccountId, Moment, BlockNumber, Balance> + AnchorApi<Block>,
+	M: Metadata + Default,
+{
+	let mut io = create_light(client.clone());
+	io.extend_with(PoeProofApi::to_delegate(Poe::new(client)));
+	io
+}
@@ -0,0 +1,94 @@
+//! A minimal blocking JSON-RPC client over WebSocket, used by the subcommands that talk to a
+//! running node.
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use serde_json::{json, Value};
+
+/// A WebSocket connection to the RPC server of a node.
+pub struct RpcClient {
+	out: ws::Sender,
+	messages: Receiver<String>,
+	// Messages received while waiting for another one.
+	pending: VecDeque<Value>,
+	next_id: u64,
+}
+
+impl RpcClient {
+	/// Connect to the node listening at `url`.
+	pub fn connect(url: &str) -> Result<Self, String> {
+		let (out_tx, out_rx) = mpsc::channel();
+		let (message_tx, messages) = mpsc::channel();
+		let url = url.to_string();
+
+		thread::spawn(move || {
+			let result = ws::connect(url.clone(), |out| {
+				let _ = out_tx.send(Ok(out));
+				let message_tx = message_tx.clone();
+				move |message: ws::Message| {
+					if let Ok(text) = message.into_text() {
+						let _ = message_tx.send(text);
+					}
+					Ok(())
+				}
+			});
+			if let Err(err) = result {
+				let _ = out_tx.send(Err(format!("Unable to connect to {}: {}", url, err)));
+			}
+		});
+
+		let out = out_rx.recv().map_err(|_| "Unable to connect to the node".to_string())??;
+		Ok(RpcClient { out, messages, pending: VecDeque::new(), next_id: 0 })
+	}
+
+	/// Call `method` and wait for its result.
+	pub fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+		let id = self.next_id;
+		self.next_id += 1;
+
+		let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+		self.out.send(request.to_string()).map_err(|err| format!("Unable to send {}: {}", method, err))?;
+
+		loop {
+			let message = self.next_message()?;
+			if message["id"] != id {
+				self.pending.push_back(message);
+				continue;
+			}
+			return match message.get("error") {
+				Some(error) => Err(format!("{} failed: {}", method, error)),
+				None => Ok(message["result"].clone()),
+			};
+		}
+	}
+
+	/// Wait for the next notification of `subscription`, as returned by the subscribing method.
+	pub fn notification(&mut self, subscription: &Value) -> Result<Value, String> {
+		let is_notification = |message: &Value| message["params"]["subscription"] == *subscription;
+
+		if let Some(index) = self.pending.iter().position(is_notification) {
+			let message = self.pending.remove(index).expect("index is that of a pending message; qed");
+			return Ok(message["params"]["result"].clone());
+		}
+
+		loop {
+			let message = self.next_message()?;
+			if is_notification(&message) {
+				return Ok(message["params"]["result"].clone());
+			}
+			self.pending.push_back(message);
+		}
+	}
+
+	fn next_message(&mut self) -> Result<Value, String> {
+		let text = self.messages.recv().map_err(|_| "The connection to the node was closed".to_string())?;
+		serde_json::from_str(&text).map_err(|err| format!("Invalid message from the node: {}", err))
+	}
+}
+
+impl Drop for RpcClient {
+	fn drop(&mut self) {
+		let _ = self.out.close(ws::CloseCode::Normal);
+	}
+}
@@ -70,7 +70,8 @@ macro_rules! new_full_start {
 				tasks_to_spawn = Some(vec![Box::new(pruning_task)]);
 
 				Ok(import_queue)
-			})?;
+			})?
+			.with_rpc_extensions(|client, _pool| crate::rpc::create_full(client))?;
 
 		(builder, import_setup, inherent_data_providers, tasks_to_spawn)
 	}}
@@ -222,6 +223,8 @@ pub fn new_light<C: Send + Default + 'static>(config: Configuration<C, GenesisCo
 
 			Ok((import_queue, finality_proof_request_builder))
 		})?
+		// Light clients can't prove storage, so `poe_getClaimProof` is only served by full nodes
+		.with_rpc_extensions(|client, _pool| crate::rpc::create_light(client))?
 		.with_network_protocol(|_| Ok(NodeProtocol::new()))?
 		.with_finality_proof_provider(|client|
 			Ok(Arc::new(GrandpaFinalityProofProvider::new(client.clone(), client)) as _)